@@ -0,0 +1,333 @@
+use crate::budget::{CancelToken, Limits};
+use crate::cache::DayCache;
+use crate::error::Error;
+use crate::params::PuzzleParams;
+use crate::runner;
+use crate::solver::{self, Answer, Part};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Known-correct answers for stored inputs, keyed by day, then input id:
+///
+/// ```toml
+/// [day14.example]
+/// input = "examples/day14.txt"
+/// params = { width = 11, height = 7 }
+/// part1 = 12
+///
+/// [day14.real]
+/// part1 = 231019008
+/// part2 = 8280
+/// ```
+///
+/// Input paths are relative to the answers file and default to `day<N>.txt`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AnswerStore {
+    inputs: Vec<StoredInput>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoredInput {
+    pub day: u8,
+    pub id: String,
+    pub path: PathBuf,
+    pub params: PuzzleParams,
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl StoredInput {
+    pub fn expected(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawInput {
+    input: Option<PathBuf>,
+    #[serde(default)]
+    params: BTreeMap<String, toml::Value>,
+    part1: Option<toml::Value>,
+    part2: Option<toml::Value>,
+}
+
+/// Answers may be written as TOML strings or numbers.
+fn value_to_string(value: toml::Value) -> String {
+    match value {
+        toml::Value::String(s) => s,
+        value => value.to_string(),
+    }
+}
+
+impl AnswerStore {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|e| Error::io(path, &e))?;
+        Self::parse(&text, path.parent().unwrap_or_else(|| Path::new("")))
+    }
+
+    /// Parses the contents of an answers file, resolving input paths against `dir`.
+    pub fn parse(text: &str, dir: &Path) -> Result<Self, Error> {
+        let raw: BTreeMap<String, BTreeMap<String, RawInput>> =
+            toml::from_str(text).map_err(|e| Error::InvalidAnswers(e.to_string()))?;
+
+        let mut inputs = vec![];
+        for (key, entries) in raw {
+            let day = key
+                .strip_prefix("day")
+                .and_then(|day| day.parse().ok())
+                .ok_or_else(|| {
+                    Error::InvalidAnswers(format!("expected a table named `day<N>`, got `{key}`"))
+                })?;
+            solver::solver(day)?;
+
+            for (id, entry) in entries {
+                let mut params = PuzzleParams::new();
+                for (key, value) in entry.params {
+                    params.set(&key, value_to_string(value));
+                }
+
+                inputs.push(StoredInput {
+                    day,
+                    id,
+                    path: dir.join(
+                        entry
+                            .input
+                            .unwrap_or_else(|| PathBuf::from(format!("day{day}.txt"))),
+                    ),
+                    params,
+                    part1: entry.part1.map(value_to_string),
+                    part2: entry.part2.map(value_to_string),
+                });
+            }
+        }
+
+        inputs.sort_by_key(|input| input.day);
+        Ok(Self { inputs })
+    }
+
+    pub fn inputs(&self) -> &[StoredInput] {
+        &self.inputs
+    }
+
+    /// Runs every registered solver, or only `day`, against all of its stored inputs.
+    ///
+    /// Each part runs within `limits`; panics and timeouts are recorded as
+    /// errors of that part and the other checks go on. A day's cache is kept
+    /// across all of its inputs.
+    pub fn verify(&self, day: Option<u8>, limits: &Limits, token: &CancelToken) -> Vec<Check> {
+        let mut checks = vec![];
+
+        for solver in solver::solvers() {
+            let solver_day = solver.day();
+            if day.is_some_and(|day| day != solver_day) {
+                continue;
+            }
+
+            let inputs = self.inputs.iter().filter(|input| input.day == solver_day);
+            let before = checks.len();
+            let mut cache = solver.new_cache();
+
+            for input in inputs {
+                match std::fs::read_to_string(&input.path) {
+                    Ok(text) => {
+                        checks.extend(check(input, &text, limits, token, cache.as_deref_mut()));
+                    }
+                    Err(e) => checks.extend(Part::ALL.map(|part| Check {
+                        day: solver_day,
+                        part,
+                        id: Some(input.id.clone()),
+                        answer: None,
+                        status: Status::Error(Error::io(&input.path, &e)),
+                        elapsed: Duration::ZERO,
+                    })),
+                }
+            }
+
+            if checks.len() == before {
+                checks.extend(Part::ALL.map(|part| Check {
+                    day: solver_day,
+                    part,
+                    id: None,
+                    answer: None,
+                    status: Status::NoInput,
+                    elapsed: Duration::ZERO,
+                }));
+            }
+        }
+
+        checks
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    Pass,
+    Fail {
+        expected: String,
+    },
+    /// The answer was computed, but none is stored to compare against.
+    Missing,
+    /// The day has no stored inputs at all.
+    NoInput,
+    Error(Error),
+}
+
+/// The outcome of solving one part of one stored input.
+#[derive(Clone, Debug)]
+pub struct Check {
+    pub day: u8,
+    pub part: Part,
+    pub id: Option<String>,
+    pub answer: Option<Answer>,
+    pub status: Status,
+    /// Time spent parsing and solving.
+    pub elapsed: Duration,
+}
+
+/// Solves both parts of `input`, whose contents are `text`, each within
+/// `limits` and sharing the day's `cache` between them.
+pub fn check(
+    input: &StoredInput,
+    text: &str,
+    limits: &Limits,
+    token: &CancelToken,
+    mut cache: Option<&mut dyn DayCache>,
+) -> Vec<Check> {
+    Part::ALL
+        .into_iter()
+        .map(|part| {
+            let (result, elapsed) = match solver::solver(input.day) {
+                Ok(solver) => {
+                    let budget = limits.start(token.clone());
+                    let run = runner::run_part(
+                        solver,
+                        part,
+                        text,
+                        &input.params,
+                        &budget,
+                        cache.as_deref_mut(),
+                    );
+                    (run.result, run.parse_time + run.solve_time)
+                }
+                Err(e) => (Err(e), Duration::ZERO),
+            };
+
+            let (answer, status) = match result {
+                Ok(answer) => {
+                    let status = match input.expected(part) {
+                        None => Status::Missing,
+                        Some(expected) if answer.to_string() == expected => Status::Pass,
+                        Some(expected) => Status::Fail {
+                            expected: expected.to_string(),
+                        },
+                    };
+                    (Some(answer), status)
+                }
+                Err(e) => (None, Status::Error(e)),
+            };
+
+            Check {
+                day: input.day,
+                part,
+                id: Some(input.id.clone()),
+                answer,
+                status,
+                elapsed,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::Exhausted;
+
+    const ANSWERS: &str = r#"
+        [day11.example]
+        input = "examples/day11.txt"
+        params = { blinks = 6 }
+        part1 = 22
+
+        [day11.wrong]
+        part1 = "55312"
+        part2 = "1"
+    "#;
+
+    #[test]
+    fn test_parse() {
+        let store = AnswerStore::parse(ANSWERS, Path::new("input")).expect("valid answers");
+        let [example, wrong] = store.inputs() else {
+            panic!("expected two inputs");
+        };
+
+        assert_eq!(example.path, Path::new("input/examples/day11.txt"));
+        assert_eq!(example.params, PuzzleParams::new().with("blinks", 6));
+        assert_eq!(example.expected(Part::One), Some("22"));
+        assert_eq!(example.expected(Part::Two), None);
+        assert_eq!(wrong.path, Path::new("input/day11.txt"));
+
+        let err = AnswerStore::parse("[eleven.x]\npart1 = 1", Path::new(""));
+        assert!(matches!(err, Err(Error::InvalidAnswers(_))));
+        let err = AnswerStore::parse("[day26.x]\npart1 = 1", Path::new(""));
+        assert_eq!(err, Err(Error::UnknownDay(26)));
+    }
+
+    #[test]
+    fn test_check() {
+        let store = AnswerStore::parse(ANSWERS, Path::new("")).expect("valid answers");
+        let mut cache = solver::solver(11).expect("registered").new_cache();
+        let mut statuses = |input| {
+            let limits = Limits::default();
+            check(
+                input,
+                "125 17",
+                &limits,
+                &CancelToken::new(),
+                cache.as_deref_mut(),
+            )
+            .into_iter()
+            .map(|c| c.status)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            statuses(&store.inputs()[0]),
+            vec![Status::Pass, Status::Missing]
+        );
+        assert_eq!(
+            statuses(&store.inputs()[1]),
+            vec![
+                Status::Pass,
+                Status::Fail {
+                    expected: "1".to_string()
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_timeout() {
+        let store = AnswerStore::parse("[day17.loop]\npart1 = 1", Path::new("")).expect("valid");
+        let text = "Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 3,0";
+        let limits = Limits {
+            timeout: Some(Duration::ZERO),
+            max_steps: None,
+        };
+
+        let checks = check(&store.inputs()[0], text, &limits, &CancelToken::new(), None);
+        assert!(matches!(
+            checks[0].status,
+            Status::Error(Error::GaveUp {
+                reason: Exhausted::Timeout,
+                ..
+            })
+        ));
+    }
+}
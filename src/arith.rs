@@ -0,0 +1,133 @@
+//! Arithmetic for results that can outgrow a machine word.
+//!
+//! By default [`Int`] is an `i64` whose operations wrap, as they do in release
+//! builds. The `checked` feature makes them fail with [`Overflow`] instead,
+//! and the `bigint` feature swaps in arbitrary-precision integers, which never
+//! overflow.
+
+use crate::error::Error;
+use crate::solver::Answer;
+use std::fmt;
+
+#[cfg(not(feature = "bigint"))]
+pub type Int = i64;
+
+#[cfg(feature = "bigint")]
+pub type Int = num::BigInt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "arithmetic overflow")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// The answer of a part computed with [`Int`], reporting overflow as an error.
+pub fn answer<T: Into<Answer>>(day: u8, result: Result<T, Overflow>) -> Result<Answer, Error> {
+    result
+        .map(Into::into)
+        .map_err(|Overflow| Error::Overflow { day })
+}
+
+pub fn int<T>(n: T) -> Result<Int, Overflow>
+where
+    Int: TryFrom<T>,
+{
+    Int::try_from(n).map_err(|_| Overflow)
+}
+
+pub fn sum(items: impl IntoIterator<Item = Result<Int, Overflow>>) -> Result<Int, Overflow> {
+    items
+        .into_iter()
+        .try_fold(Int::default(), |acc, item| add(&acc, &item?))
+}
+
+#[cfg(not(feature = "bigint"))]
+mod ops {
+    use super::{Int, Overflow};
+
+    pub fn add(a: &Int, b: &Int) -> Result<Int, Overflow> {
+        wrap(a.checked_add(*b), a.wrapping_add(*b))
+    }
+
+    pub fn sub(a: &Int, b: &Int) -> Result<Int, Overflow> {
+        wrap(a.checked_sub(*b), a.wrapping_sub(*b))
+    }
+
+    pub fn mul(a: &Int, b: &Int) -> Result<Int, Overflow> {
+        wrap(a.checked_mul(*b), a.wrapping_mul(*b))
+    }
+
+    /// `a / b` if `b` divides `a`.
+    pub fn div_exact(a: &Int, b: &Int) -> Option<Int> {
+        a.checked_rem(*b)
+            .filter(|&rem| rem == 0)
+            .and_then(|_| a.checked_div(*b))
+    }
+
+    fn wrap(checked: Option<Int>, wrapped: Int) -> Result<Int, Overflow> {
+        if cfg!(feature = "checked") {
+            checked.ok_or(Overflow)
+        } else {
+            Ok(wrapped)
+        }
+    }
+}
+
+#[cfg(feature = "bigint")]
+mod ops {
+    use super::{Int, Overflow};
+    use num::{Integer, Zero};
+
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn add(a: &Int, b: &Int) -> Result<Int, Overflow> {
+        Ok(a + b)
+    }
+
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn sub(a: &Int, b: &Int) -> Result<Int, Overflow> {
+        Ok(a - b)
+    }
+
+    #[allow(clippy::unnecessary_wraps)]
+    pub fn mul(a: &Int, b: &Int) -> Result<Int, Overflow> {
+        Ok(a * b)
+    }
+
+    /// `a / b` if `b` divides `a`.
+    pub fn div_exact(a: &Int, b: &Int) -> Option<Int> {
+        if b.is_zero() {
+            return None;
+        }
+        let (quot, rem) = a.div_rem(b);
+        rem.is_zero().then_some(quot)
+    }
+}
+
+pub use ops::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overflow() {
+        let res = add(&Int::from(i64::MAX), &Int::from(1));
+
+        if cfg!(feature = "bigint") {
+            assert_eq!(res, int(i128::from(i64::MAX) + 1));
+        } else if cfg!(feature = "checked") {
+            assert_eq!(res, Err(Overflow));
+        } else {
+            assert_eq!(res, int(i64::MIN));
+        }
+
+        assert_eq!(div_exact(&Int::from(12), &Int::from(4)), Some(Int::from(3)));
+        assert_eq!(div_exact(&Int::from(12), &Int::from(5)), None);
+        assert_eq!(div_exact(&Int::from(12), &Int::from(0)), None);
+    }
+}
@@ -0,0 +1,212 @@
+use crate::error::Error;
+use crate::params::PuzzleParams;
+use crate::solver::{DynSolver, Part};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Slowdowns smaller than this are treated as measurement noise.
+pub const NOISE_FLOOR: Duration = Duration::from_micros(50);
+
+/// Median generator and solver times of one part.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    pub day: u8,
+    pub part: u8,
+    pub parse_ns: u64,
+    pub solve_ns: u64,
+}
+
+impl Timing {
+    pub const fn parse_time(&self) -> Duration {
+        Duration::from_nanos(self.parse_ns)
+    }
+
+    pub const fn solve_time(&self) -> Duration {
+        Duration::from_nanos(self.solve_ns)
+    }
+}
+
+/// Saved timings that later runs are compared against.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Baseline {
+    pub timings: Vec<Timing>,
+}
+
+impl Baseline {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path).map_err(|e| Error::io(path, &e))?;
+        serde_json::from_str(&text).map_err(|e| Error::InvalidBaseline(e.to_string()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| Error::InvalidBaseline(e.to_string()))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| Error::io(dir, &e))?;
+        }
+        std::fs::write(path, json + "\n").map_err(|e| Error::io(path, &e))
+    }
+
+    pub fn get(&self, day: u8, part: u8) -> Option<&Timing> {
+        self.timings.iter().find(|t| t.day == day && t.part == part)
+    }
+
+    /// Replaces the timing for the same day and part, keeping the rest.
+    pub fn update(&mut self, timing: Timing) {
+        self.timings
+            .retain(|t| (t.day, t.part) != (timing.day, timing.part));
+        self.timings.push(timing);
+        self.timings.sort_by_key(|t| (t.day, t.part));
+    }
+}
+
+fn median(timings: &mut [Duration]) -> u64 {
+    timings.sort();
+    let median = timings.get(timings.len() / 2).copied().unwrap_or_default();
+    u64::try_from(median.as_nanos()).unwrap_or(u64::MAX)
+}
+
+/// Times `iterations` runs of the generator and the solver separately.
+pub fn measure(
+    solver: &dyn DynSolver,
+    part: Part,
+    input: &str,
+    params: &PuzzleParams,
+    iterations: u32,
+) -> Result<Timing, Error> {
+    let mut parse_timings = vec![];
+    let mut solve_timings = vec![];
+
+    for _ in 0..iterations.max(1) {
+        let start = Instant::now();
+        let parsed = solver.parse(part, input)?;
+        parse_timings.push(start.elapsed());
+
+        let start = Instant::now();
+        solver.solve_with(part, &parsed, params)?;
+        solve_timings.push(start.elapsed());
+    }
+
+    Ok(Timing {
+        day: solver.day(),
+        part: part.number(),
+        parse_ns: median(&mut parse_timings),
+        solve_ns: median(&mut solve_timings),
+    })
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Stage {
+    Parse,
+    Solve,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Parse => write!(f, "parse"),
+            Self::Solve => write!(f, "solve"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Regression {
+    pub day: u8,
+    pub part: u8,
+    pub stage: Stage,
+    pub baseline: Duration,
+    pub current: Duration,
+}
+
+impl Regression {
+    /// The slowdown relative to the baseline, rounded down.
+    pub fn percent(&self) -> u128 {
+        let (base, current) = (self.baseline.as_nanos(), self.current.as_nanos());
+        current.saturating_sub(base) * 100 / base.max(1)
+    }
+}
+
+/// Timings in `current` that are more than `threshold` percent slower than `baseline`.
+///
+/// Parts missing from the baseline are not reported.
+pub fn compare(baseline: &Baseline, current: &[Timing], threshold: u32) -> Vec<Regression> {
+    let mut regressions = vec![];
+
+    for timing in current {
+        let Some(base) = baseline.get(timing.day, timing.part) else {
+            continue;
+        };
+
+        let stages = [
+            (Stage::Parse, base.parse_time(), timing.parse_time()),
+            (Stage::Solve, base.solve_time(), timing.solve_time()),
+        ];
+
+        for (stage, base, current) in stages {
+            let slower = current.saturating_sub(base);
+            let allowed = base * (100 + threshold) / 100;
+
+            if current > allowed && slower > NOISE_FLOOR {
+                regressions.push(Regression {
+                    day: timing.day,
+                    part: timing.part,
+                    stage,
+                    baseline: base,
+                    current,
+                });
+            }
+        }
+    }
+
+    regressions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver;
+
+    fn timing(day: u8, parse_us: u64, solve_us: u64) -> Timing {
+        Timing {
+            day,
+            part: 1,
+            parse_ns: parse_us * 1000,
+            solve_ns: solve_us * 1000,
+        }
+    }
+
+    #[test]
+    fn test_measure() {
+        let solver = solver::solver(1).expect("registered");
+        let res = measure(solver, Part::Two, "3   4\n4   3", &PuzzleParams::new(), 3);
+        assert!(res.is_ok_and(|t| t.day == 1 && t.part == 2));
+    }
+
+    #[test]
+    fn test_compare() {
+        let mut baseline = Baseline::default();
+        baseline.update(timing(6, 100, 1000));
+        baseline.update(timing(6, 100, 2000));
+        baseline.update(timing(5, 100, 100));
+        assert_eq!(baseline.timings.len(), 2);
+
+        let current = [timing(5, 140, 120), timing(6, 100, 2500), timing(7, 1, 1)];
+        let res = compare(&baseline, &current, 20);
+
+        assert_eq!(
+            res,
+            vec![Regression {
+                day: 6,
+                part: 1,
+                stage: Stage::Solve,
+                baseline: Duration::from_millis(2),
+                current: Duration::from_micros(2500),
+            }]
+        );
+        assert_eq!(res[0].percent(), 25);
+        assert!(compare(&baseline, &current, 30).is_empty());
+    }
+}
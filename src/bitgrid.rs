@@ -0,0 +1,242 @@
+//! Bit-packed sets of grid positions, for visited and occupancy state that
+//! would otherwise be a `Grid<bool>` or a `HashSet` of positions.
+
+use crate::pos::{Dir, Pos};
+
+const WORD_BITS: usize = u64::BITS as usize;
+
+/// A set of positions in a `rows` × `columns` grid, one bit per position.
+///
+/// Each row starts on a word boundary, so the bitwise operations work a row
+/// at a time and never carry bits from one row into the next.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BitGrid {
+    pub rows: usize,
+    pub columns: usize,
+    row_words: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    /// An empty set.
+    pub fn new(rows: usize, columns: usize) -> Self {
+        let row_words = columns.div_ceil(WORD_BITS);
+        Self {
+            rows,
+            columns,
+            row_words,
+            words: vec![0; rows * row_words],
+        }
+    }
+
+    pub const fn within_bounds(&self, (r, c): (usize, usize)) -> bool {
+        r < self.rows && c < self.columns
+    }
+
+    fn index(&self, (r, c): (usize, usize)) -> Option<(usize, u64)> {
+        self.within_bounds((r, c))
+            .then(|| (r * self.row_words + c / WORD_BITS, 1 << (c % WORD_BITS)))
+    }
+
+    fn expect_index(&self, pos: (usize, usize)) -> (usize, u64) {
+        self.index(pos).unwrap_or_else(|| {
+            panic!(
+                "position {pos:?} outside a {}x{} grid",
+                self.rows, self.columns
+            )
+        })
+    }
+
+    /// Whether `pos` is in the set. Positions outside the grid never are.
+    pub fn contains(&self, pos: impl Into<(usize, usize)>) -> bool {
+        self.index(pos.into())
+            .is_some_and(|(word, bit)| self.words[word] & bit != 0)
+    }
+
+    /// Adds `pos`, returning whether it was not in the set yet.
+    pub fn insert(&mut self, pos: impl Into<(usize, usize)>) -> bool {
+        let (word, bit) = self.expect_index(pos.into());
+        let added = self.words[word] & bit == 0;
+        self.words[word] |= bit;
+        added
+    }
+
+    /// Removes `pos`, returning whether it was in the set.
+    pub fn remove(&mut self, pos: impl Into<(usize, usize)>) -> bool {
+        let (word, bit) = self.expect_index(pos.into());
+        let removed = self.words[word] & bit != 0;
+        self.words[word] &= !bit;
+        removed
+    }
+
+    /// Empties the set, keeping its memory.
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// The words of row `r`, lowest column in the lowest bit.
+    pub fn row(&self, r: usize) -> &[u64] {
+        &self.words[r * self.row_words..(r + 1) * self.row_words]
+    }
+
+    /// The positions in the set, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = Pos> + '_ {
+        (0..self.rows).flat_map(move |r| {
+            self.row(r).iter().enumerate().flat_map(move |(i, &word)| {
+                std::iter::successors(Some(word).filter(|&w| w != 0), |&w| {
+                    Some(w & (w - 1)).filter(|&w| w != 0)
+                })
+                .map(move |w| Pos::new(r, i * WORD_BITS + w.trailing_zeros() as usize))
+            })
+        })
+    }
+
+    fn zip_rows(&mut self, other: &Self, op: impl Fn(u64, u64) -> u64) {
+        assert_eq!(
+            (self.rows, self.columns),
+            (other.rows, other.columns),
+            "grids of different sizes"
+        );
+        for (word, &other) in self.words.iter_mut().zip(&other.words) {
+            *word = op(*word, other);
+        }
+    }
+
+    /// Adds the positions of `other`, which must have the same size.
+    pub fn union_with(&mut self, other: &Self) {
+        self.zip_rows(other, |a, b| a | b);
+    }
+
+    /// Keeps the positions that are also in `other`, which must have the same size.
+    pub fn intersect_with(&mut self, other: &Self) {
+        self.zip_rows(other, |a, b| a & b);
+    }
+
+    /// Removes the positions of `other`, which must have the same size.
+    pub fn difference_with(&mut self, other: &Self) {
+        self.zip_rows(other, |a, b| a & !b);
+    }
+}
+
+impl<P: Into<(usize, usize)>> Extend<P> for BitGrid {
+    fn extend<I: IntoIterator<Item = P>>(&mut self, positions: I) {
+        for pos in positions {
+            self.insert(pos);
+        }
+    }
+}
+
+/// A set of (position, direction) states, one [`BitGrid`] per direction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirBitGrid {
+    grids: [BitGrid; 4],
+}
+
+impl DirBitGrid {
+    pub fn new(rows: usize, columns: usize) -> Self {
+        Self {
+            grids: std::array::from_fn(|_| BitGrid::new(rows, columns)),
+        }
+    }
+
+    pub fn contains(&self, pos: impl Into<(usize, usize)>, dir: Dir) -> bool {
+        self.grids[dir as usize].contains(pos)
+    }
+
+    /// Adds the state, returning whether it was not in the set yet.
+    pub fn insert(&mut self, pos: impl Into<(usize, usize)>, dir: Dir) -> bool {
+        self.grids[dir as usize].insert(pos)
+    }
+
+    pub fn clear(&mut self) {
+        self.grids.iter_mut().for_each(BitGrid::clear);
+    }
+
+    pub fn count(&self) -> usize {
+        self.grids.iter().map(BitGrid::count).sum()
+    }
+
+    /// The positions reached in any direction.
+    pub fn positions(&self) -> BitGrid {
+        let [first, rest @ ..] = &self.grids;
+        rest.iter().fold(first.clone(), |mut acc, grid| {
+            acc.union_with(grid);
+            acc
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_bitgrid() {
+        let mut grid = BitGrid::new(3, 70);
+        assert!(grid.is_empty());
+        assert!(grid.insert((0, 0)));
+        assert!(grid.insert(Pos::new(1, 69)));
+        assert!(grid.insert((2, 64)));
+        assert!(!grid.insert((2, 64)));
+        grid.extend([(2, 64)]);
+
+        assert!(grid.contains((1, 69)));
+        assert!(!grid.contains((1, 70)));
+        assert!(!grid.contains((3, 0)));
+        assert_eq!(grid.count(), 3);
+        assert_eq!(grid.row(2), [0, 1]);
+        assert_eq!(
+            grid.iter().collect_vec(),
+            [Pos::new(0, 0), Pos::new(1, 69), Pos::new(2, 64)]
+        );
+
+        let mut other = BitGrid::new(3, 70);
+        other.insert((0, 0));
+        other.insert((0, 1));
+
+        let mut union = grid.clone();
+        union.union_with(&other);
+        assert_eq!(union.count(), 4);
+
+        let mut both = grid.clone();
+        both.intersect_with(&other);
+        assert_eq!(both.iter().collect_vec(), [Pos::new(0, 0)]);
+
+        grid.difference_with(&other);
+        assert_eq!(grid.count(), 2);
+        assert!(grid.remove((1, 69)));
+        assert!(!grid.remove((1, 69)));
+
+        grid.clear();
+        assert!(grid.is_empty());
+    }
+
+    #[test]
+    fn test_dir_bitgrid() {
+        let mut states = DirBitGrid::new(2, 2);
+        assert!(states.insert((0, 1), Dir::North));
+        assert!(states.insert((0, 1), Dir::West));
+        assert!(states.insert((1, 1), Dir::West));
+        assert!(!states.insert((0, 1), Dir::North));
+
+        assert!(states.contains((1, 1), Dir::West));
+        assert!(!states.contains((1, 1), Dir::East));
+        assert_eq!(states.count(), 3);
+        assert_eq!(
+            states.positions().iter().collect_vec(),
+            [Pos::new(0, 1), Pos::new(1, 1)]
+        );
+
+        states.clear();
+        assert_eq!(states.count(), 0);
+    }
+}
@@ -0,0 +1,163 @@
+//! Limits for solvers that iterate until something happens, which may be never
+//! on a malformed input.
+//!
+//! Such solvers call [`Budget::step`] once per iteration and give up with
+//! [`Exhausted`] when it fails. Solvers that always finish ignore the budget,
+//! so a time limit cannot interrupt them.
+
+use crate::error::Error;
+use crate::solver::Answer;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often [`Budget::step`] looks at the clock.
+const CLOCK_INTERVAL: u64 = 1 << 10;
+
+/// Why a solver gave up.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Exhausted {
+    Cancelled,
+    Timeout,
+    Steps(u64),
+}
+
+impl fmt::Display for Exhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::Timeout => write!(f, "out of time"),
+            Self::Steps(steps) => write!(f, "out of steps after {steps}"),
+        }
+    }
+}
+
+impl std::error::Error for Exhausted {}
+
+/// The answer of a part that may give up, reporting that as an error.
+pub fn answer<T: Into<Answer>>(day: u8, result: Result<T, Exhausted>) -> Result<Answer, Error> {
+    result
+        .map(Into::into)
+        .map_err(|reason| Error::GaveUp { day, reason })
+}
+
+/// Stops every [`Budget`] holding a clone of it.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Cancels the token on Ctrl-C. A second Ctrl-C exits the process, for
+    /// solvers that never look at their budget.
+    pub fn cancel_on_interrupt(&self) -> std::io::Result<()> {
+        use signal_hook::consts::SIGINT;
+        use signal_hook::flag;
+
+        flag::register_conditional_shutdown(SIGINT, 130, Arc::clone(&self.0))?;
+        flag::register(SIGINT, Arc::clone(&self.0))?;
+        Ok(())
+    }
+}
+
+/// The limits to apply to each part, before its clock starts.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub timeout: Option<Duration>,
+    pub max_steps: Option<u64>,
+}
+
+impl Limits {
+    /// A budget with these limits, whose time starts now.
+    pub fn start(&self, token: CancelToken) -> Budget {
+        Budget {
+            token,
+            deadline: self.timeout.map(|timeout| Instant::now() + timeout),
+            max_steps: self.max_steps,
+            steps: AtomicU64::new(0),
+        }
+    }
+}
+
+/// The steps and time one part may spend. Unlimited by default.
+#[derive(Debug, Default)]
+pub struct Budget {
+    token: CancelToken,
+    deadline: Option<Instant>,
+    max_steps: Option<u64>,
+    steps: AtomicU64,
+}
+
+impl Budget {
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    pub fn with_max_steps(max_steps: u64) -> Self {
+        Limits {
+            max_steps: Some(max_steps),
+            ..Limits::default()
+        }
+        .start(CancelToken::new())
+    }
+
+    pub fn steps(&self) -> u64 {
+        self.steps.load(Ordering::Relaxed)
+    }
+
+    /// Accounts for one iteration, failing once any limit is reached.
+    pub fn step(&self) -> Result<(), Exhausted> {
+        let steps = self.steps.fetch_add(1, Ordering::Relaxed) + 1;
+
+        if self.token.is_cancelled() {
+            return Err(Exhausted::Cancelled);
+        }
+        if self.max_steps.is_some_and(|max_steps| steps > max_steps) {
+            return Err(Exhausted::Steps(steps - 1));
+        }
+        if steps.is_multiple_of(CLOCK_INTERVAL)
+            && self.deadline.is_some_and(|d| Instant::now() >= d)
+        {
+            return Err(Exhausted::Timeout);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget() {
+        let budget = Budget::with_max_steps(3);
+        assert!((0..3).all(|_| budget.step().is_ok()));
+        assert_eq!(budget.step(), Err(Exhausted::Steps(3)));
+
+        let token = CancelToken::new();
+        let budget = Limits::default().start(token.clone());
+        assert_eq!(budget.step(), Ok(()));
+        token.cancel();
+        assert_eq!(budget.step(), Err(Exhausted::Cancelled));
+
+        let limits = Limits {
+            timeout: Some(Duration::ZERO),
+            max_steps: None,
+        };
+        let budget = limits.start(CancelToken::new());
+        let res = (0..CLOCK_INTERVAL).try_for_each(|_| budget.step());
+        assert_eq!(res, Err(Exhausted::Timeout));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::error::Error;
+use std::any::Any;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// A memoization table owned by the code that solves, rather than a global.
+///
+/// Keep one alive to reuse results across inputs, or drop or
+/// [`clear`](Self::clear) it to free the memory.
+#[derive(Clone, Debug)]
+pub struct Cache<K, V> {
+    map: HashMap<K, V>,
+}
+
+impl<K, V> Default for Cache<K, V> {
+    fn default() -> Self {
+        Self {
+            map: HashMap::new(),
+        }
+    }
+}
+
+impl<K: Eq + Hash, V> Cache<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.map.get(key)
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        self.map.insert(key, value);
+    }
+
+    /// The cached value for `key`, computing it with `f` on a miss.
+    ///
+    /// `f` cannot use the cache itself; recursive callers combine
+    /// [`get`](Self::get) and [`insert`](Self::insert) instead.
+    pub fn get_or_insert_with(&mut self, key: K, f: impl FnOnce() -> V) -> &V {
+        self.map.entry(key).or_insert_with(f)
+    }
+
+    /// The number of cached entries.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+/// The memoization of one day, which the runner keeps between solves.
+///
+/// It may only remember what does not depend on the input, so that the parts
+/// of an input can share it.
+pub trait DayCache: Any + Send {
+    /// The number of cached entries.
+    fn cache_size(&self) -> usize;
+
+    fn clear_cache(&mut self);
+}
+
+/// `cache` as the `T` that `day` made it as.
+pub fn downcast<T: DayCache>(day: u8, cache: &mut dyn DayCache) -> Result<&mut T, Error> {
+    (cache as &mut dyn Any)
+        .downcast_mut()
+        .ok_or(Error::CacheMismatch { day })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache() {
+        let mut cache = Cache::new();
+        let mut calls = 0;
+        for _ in 0..2 {
+            cache.get_or_insert_with(1, || {
+                calls += 1;
+                "one"
+            });
+        }
+        cache.insert(2, "two");
+
+        assert_eq!(calls, 1);
+        assert_eq!((cache.len(), cache.get(&2)), (2, Some(&"two")));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
@@ -1,22 +1,31 @@
+use crate::error::{Error, ParseError};
+use crate::parse;
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
+use std::io::BufRead;
 
 #[aoc_generator(day01)]
-pub fn generate(s: &str) -> Option<(Vec<usize>, Vec<usize>)> {
+pub fn generate(s: &str) -> Result<(Vec<usize>, Vec<usize>), ParseError> {
+    parse::from_text(1, s, generate_from)
+}
+
+pub fn generate_from<R: BufRead>(reader: R) -> Result<(Vec<usize>, Vec<usize>), Error> {
     let mut left = vec![];
     let mut right = vec![];
 
-    for line in s.lines() {
+    parse::for_each_line(1, reader, |src, line| {
         let whs = line.split_whitespace().collect::<Vec<_>>();
+        let [l, r] = whs[..] else {
+            return Err(src.error(line, "expected two whitespace separated numbers"));
+        };
 
-        let l = whs[0].parse::<usize>().ok()?;
-        let r = whs[1].parse::<usize>().ok()?;
-
-        left.push(l);
-        right.push(r);
-    }
+        left.push(src.number(l)?);
+        right.push(src.number(r)?);
+        Ok(())
+    })?;
 
-    Some((left, right))
+    Ok((left, right))
 }
 
 #[aoc(day01, part1)]
@@ -35,9 +44,30 @@ pub fn part2((left, right): &(Vec<usize>, Vec<usize>)) -> usize {
         .sum()
 }
 
+pub struct Day01;
+
+impl Solver for Day01 {
+    const DAY: u8 = 1;
+
+    type Input = (Vec<usize>, Vec<usize>);
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "3   4
                               4   3\n\
@@ -59,4 +89,19 @@ mod tests {
         let res = part2(&gen);
         assert_eq!(res, 31);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let err = generate("3   4\n4   x").expect_err("invalid input");
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 5, "x"));
+
+        let err = generate("3   4\n4").expect_err("invalid input");
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(1, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(11)));
+    }
 }
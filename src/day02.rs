@@ -1,34 +1,35 @@
+use crate::error::{Error, ParseError};
+use crate::parse::Source;
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use std::iter::Iterator;
 
 #[derive(Clone, Debug)]
 pub struct Input {
-    levels: Vec<usize>,
+    levels: Vec<isize>,
 }
 
 #[aoc_generator(day02)]
-pub fn generate(s: &str) -> Vec<Input> {
+pub fn generate(s: &str) -> Result<Vec<Input>, ParseError> {
+    let src = Source::new(2, s);
+
     let mut inputs = vec![];
 
     for line in s.lines() {
         let nums = line
             .split_whitespace()
-            .map(|it| it.parse::<usize>().expect("whitespace separated numbers"))
-            .collect_vec();
+            .map(|it| src.number(it))
+            .try_collect()?;
         inputs.push(Input { levels: nums });
     }
 
-    inputs
+    Ok(inputs)
 }
 
 impl Input {
-    #[allow(clippy::cast_possible_wrap)]
     fn pairwise_diff(&self) -> Vec<isize> {
-        self.levels
-            .windows(2)
-            .map(|it| it[0] as isize - it[1] as isize)
-            .collect()
+        self.levels.windows(2).map(|it| it[0] - it[1]).collect()
     }
 
     fn is_safe(&self) -> bool {
@@ -59,9 +60,31 @@ pub fn part2(inp: &[Input]) -> usize {
     inp.iter().filter(|it| it.safe_with_removing()).count()
 }
 
+pub struct Day02;
+
+impl Solver for Day02 {
+    const DAY: u8 = 2;
+
+    type Input = Vec<Input>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "7 6 4 2 1\n\
                               1 2 7 8 9\n\
@@ -72,15 +95,21 @@ mod tests {
 
     #[test]
     fn test_p1() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part1(&gen);
         assert_eq!(res, 2);
     }
 
     #[test]
     fn test_p2() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part2(&gen);
         assert_eq!(res, 4);
     }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(2, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(2)));
+    }
 }
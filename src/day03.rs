@@ -1,13 +1,14 @@
+use crate::error::Error;
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::aoc;
-use lazy_static::lazy_static;
 use regex::Regex;
+use std::sync::LazyLock;
 
-lazy_static! {
-    static ref REGEX_MUL: Regex =
-        Regex::new(r"mul\((\d{1,3}),\s*(\d{1,3})\)").expect("valid regex");
-    static ref REGEX_MUL_KEYWORDS: Regex =
-        Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)|do\(\)|don't\(\)").expect("valid regex");
-}
+static REGEX_MUL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"mul\((\d{1,3}),\s*(\d{1,3})\)").expect("valid regex"));
+static REGEX_MUL_KEYWORDS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"mul\((\d{1,3}),(\d{1,3})\)|do\(\)|don't\(\)").expect("valid regex")
+});
 
 #[aoc(day03, part1)]
 pub fn part1(inp: &str) -> usize {
@@ -49,9 +50,31 @@ pub fn part2(inp: &str) -> usize {
     result
 }
 
+pub struct Day03;
+
+impl Solver for Day03 {
+    const DAY: u8 = 3;
+
+    type Input = String;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(input.to_string())
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT_P1: &str =
         "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
@@ -69,4 +92,10 @@ mod tests {
         let r = part2(TEST_INPUT_P2);
         assert_eq!(48, r);
     }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(3, Part::One, &parse::windows(TEST_INPUT_P1));
+        assert_eq!(res, Ok(Answer::Int(161)));
+    }
 }
@@ -1,43 +1,37 @@
+use crate::error::{Error, ParseError};
+use crate::grid::{self, Grid, GridExt};
+use crate::parse::Source;
+use crate::pos::{IPos, Pos};
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::{iproduct, EitherOrBoth, Itertools};
-use pathfinding::matrix::Matrix;
 use std::iter::Iterator;
 
 const MAS: [char; 3] = ['M', 'A', 'S'];
 
 #[aoc_generator(day04)]
-pub fn generate(s: &str) -> Option<Matrix<char>> {
-    let v = s.lines().map(|l| l.chars().collect_vec()).collect_vec();
-    Matrix::from_rows(v).ok()
+pub fn generate(s: &str) -> Result<Grid<char>, ParseError> {
+    grid::parse(&Source::new(4, s), s)
 }
 
-fn check_chars_in_dir(matrix: &Matrix<char>, start: (usize, usize), dir: (isize, isize)) -> bool {
+fn check_chars_in_dir(matrix: &Grid<char>, start: Pos, dir: IPos) -> bool {
     matrix
-        .in_direction(start, dir)
+        .ray(start, dir)
         .take(MAS.len())
         .zip_longest(MAS)
         .all(|it| match it {
-            EitherOrBoth::Both((r, c), expected) => matrix[(r, c)] == expected,
+            EitherOrBoth::Both(pos, expected) => matrix[pos] == expected,
             _ => false,
         })
 }
 
 #[aoc(day04, part1)]
-pub fn part1(inp: &Matrix<char>) -> usize {
+pub fn part1(inp: &Grid<char>) -> usize {
     let mut result = 0;
 
-    for (r, c) in inp.keys().filter(|&(x, y)| inp[(x, y)] == 'X') {
-        for (dx, dy) in [
-            (0, 1),
-            (1, 0),
-            (0, -1),
-            (-1, 0),
-            (1, 1),
-            (1, -1),
-            (-1, 1),
-            (-1, -1),
-        ] {
-            let n_chrs = check_chars_in_dir(inp, (r, c), (dx, dy));
+    for start in inp.find_all(&'X') {
+        for dir in IPos::ADJACENT {
+            let n_chrs = check_chars_in_dir(inp, start, dir);
             result += usize::from(n_chrs);
         }
     }
@@ -46,7 +40,7 @@ pub fn part1(inp: &Matrix<char>) -> usize {
 }
 
 #[aoc(day04, part2)]
-pub fn part2(inp: &Matrix<char>) -> usize {
+pub fn part2(inp: &Grid<char>) -> usize {
     iproduct!(1..inp.rows - 1, 1..inp.columns - 1)
         .filter(|&(x, y)| inp[(x, y)] == 'A')
         .fold(0, |acc, (r, c)| {
@@ -66,9 +60,31 @@ pub fn part2(inp: &Matrix<char>) -> usize {
         })
 }
 
+pub struct Day04;
+
+impl Solver for Day04 {
+    const DAY: u8 = 4;
+
+    type Input = Grid<char>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "MMMSXXMASM\n\
                               MSAMXMSMSA\n\
@@ -94,4 +110,10 @@ mod tests {
         let res = part2(&gen);
         assert_eq!(res, 9);
     }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(4, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(18)));
+    }
 }
@@ -1,3 +1,8 @@
+use crate::dot::Graph;
+use crate::error::{Error, ParseError};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::solver::{Answer, Part, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use pathfinding::prelude::topological_sort;
@@ -27,40 +32,43 @@ pub struct Update {
 }
 
 impl Update {
-    fn topological_sort(&self) -> Vec<usize> {
+    /// `None` if the rules contain a cycle, which the generator rejects.
+    fn topological_sort(&self) -> Option<Vec<usize>> {
         topological_sort(&self.numbers, |node: &usize| {
             self.applicable_rules
                 .get(node)
                 .map_or_else(Vec::new, std::clone::Clone::clone)
         })
-        .expect("valid topo sort exists")
+        .ok()
     }
 
     fn is_valid(&self) -> bool {
-        self.topological_sort() == self.numbers
+        self.topological_sort()
+            .is_some_and(|sorted| sorted == self.numbers)
     }
 }
 
 #[aoc_generator(day05)]
-pub fn generate(s: &str) -> Vec<Update> {
-    let spl = s.split("\n\n").collect_vec();
+pub fn generate(s: &str) -> Result<Vec<Update>, ParseError> {
+    let src = Source::new(5, s);
+    let (rules, updates) = src.sections()?;
 
     let mut order_rules = vec![];
 
-    for line in spl[0].lines() {
-        let spl = line.split('|').collect_vec();
-        let lhs = spl[0].parse::<usize>().expect("lhs|rhs");
-        let rhs = spl[1].parse::<usize>().expect("lhs|rhs");
+    for line in rules.lines() {
+        let (lhs, rhs) = src.split_once(line, "|")?;
+        let lhs = src.number(lhs)?;
+        let rhs = src.number(rhs)?;
         order_rules.push(PageOrderRule { lhs, rhs });
     }
 
-    let updates = spl[1]
+    updates
         .lines()
         .map(|l| {
             let numbers = l
                 .split(',')
-                .map(|s| s.parse::<usize>().expect("update_number"))
-                .collect_vec();
+                .map(|s| src.number(s))
+                .collect::<Result<Vec<usize>, _>>()?;
 
             let applicable_rules = order_rules
                 .iter()
@@ -68,14 +76,17 @@ pub fn generate(s: &str) -> Vec<Update> {
                 .map(|r| (r.lhs, r.rhs))
                 .into_group_map();
 
-            Update {
+            let update = Update {
                 numbers,
                 applicable_rules,
+            };
+            if update.topological_sort().is_none() {
+                return Err(src.error(l, "the ordering rules for this update form a cycle"));
             }
-        })
-        .collect_vec();
 
-    updates
+            Ok(update)
+        })
+        .collect()
 }
 
 #[aoc(day05, part1)]
@@ -92,7 +103,9 @@ pub fn part1(inp: &[Update]) -> usize {
 #[aoc(day05, part2)]
 pub fn part2(inp: &[Update]) -> usize {
     inp.iter().fold(0, |acc, it| {
-        let topo_sort = it.topological_sort();
+        let Some(topo_sort) = it.topological_sort() else {
+            return acc;
+        };
         acc + if topo_sort == it.numbers {
             0 // ignore, was already valid
         } else {
@@ -101,9 +114,67 @@ pub fn part2(inp: &[Update]) -> usize {
     })
 }
 
+pub struct Day05;
+
+impl Solver for Day05 {
+    const DAY: u8 = 5;
+    const PARAMS: &'static [&'static str] = &["update"];
+
+    type Input = Vec<Update>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    /// The rules between the pages of the `update`-th update, highlighting
+    /// its order in part 1 and the sorted order in part 2.
+    fn graph(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        params: &PuzzleParams,
+    ) -> Result<Graph, Error> {
+        let idx = params.get("update", 0)?;
+        let update = input.get(idx).ok_or_else(|| Error::InvalidParam {
+            key: "update".to_string(),
+            value: idx.to_string(),
+        })?;
+
+        let mut graph = Graph::directed();
+        for page in &update.numbers {
+            graph.node(page, page);
+        }
+        for (lhs, rhss) in update.applicable_rules.iter().sorted() {
+            for rhs in rhss {
+                graph.edge(lhs, rhs);
+            }
+        }
+
+        let order = match part {
+            Part::One => Some(update.numbers.clone()),
+            Part::Two => update.topological_sort(),
+        };
+        for (lhs, rhs) in order.iter().flatten().tuple_windows() {
+            graph.highlight_edge(lhs, rhs);
+        }
+
+        Ok(graph)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "47|53\n\
                               97|13\n\
@@ -136,15 +207,47 @@ mod tests {
 
     #[test]
     fn test_p1() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part1(&gen);
         assert_eq!(res, 143);
     }
 
     #[test]
     fn test_p2() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part2(&gen);
         assert_eq!(res, 123);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let err = generate("47|53\n97-13\n\n75,47").expect_err("invalid input");
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 1, "97-13"));
+
+        let err = generate("47|53").expect_err("invalid input");
+        assert_eq!(err.line, 1);
+
+        let err = generate("47|53\n53|47\n\n75,47\n47,53").expect_err("cyclic rules");
+        assert_eq!((err.line, err.column), (5, 1));
+    }
+
+    #[test]
+    fn test_graph() {
+        let gen = generate(TEST_INPUT).expect("valid input");
+        let params = PuzzleParams::new().with("update", 3);
+
+        let graph = Day05.graph(&gen, Part::Two, &params).expect("graph");
+        assert_eq!((graph.node_count(), graph.edge_count()), (5, 10));
+
+        let dot = graph.to_string();
+        for (lhs, rhs) in [(97, 75), (75, 47), (47, 61), (61, 53)] {
+            assert!(dot.contains(&format!("\"{lhs}\" -> \"{rhs}\" [color=red")));
+        }
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(5, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(143)));
+    }
 }
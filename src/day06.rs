@@ -1,129 +1,237 @@
+use crate::bitgrid::{BitGrid, DirBitGrid};
+use crate::error::{Error, ParseError};
+use crate::grid::{self, Grid, GridExt};
+use crate::par;
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::pos::{Dir, Pos};
+use crate::solver::{Answer, Part, Solver};
+use crate::viz::{Frame, FrameSink, NoViz};
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::{iproduct, Itertools};
-use num::Complex;
-use pathfinding::prelude::Matrix;
-use std::collections::HashSet;
+use itertools::Itertools;
+
+/// How many candidate obstructions share one set of seen states.
+const CHUNK: usize = 64;
 
 #[derive(Eq, PartialEq, Hash, Copy, Clone, Debug)]
 pub struct Guard {
-    pos: Complex<i64>,
-    direction: Complex<i64>,
+    pos: Pos,
+    direction: Dir,
 }
 
 impl Guard {
-    fn turn_right(&mut self) {
-        self.direction *= -Complex::i();
+    const fn new(pos: Pos) -> Self {
+        Self {
+            pos,
+            direction: Dir::North,
+        }
+    }
+
+    const fn turn_right(&mut self) {
+        self.direction = self.direction.turn_right();
     }
 
-    fn move_forward(&mut self) {
-        self.pos += self.direction;
+    fn move_forward(&mut self, grid: &Grid<Tile>) {
+        if let Some(next) = self.next_coord(grid) {
+            self.pos = next;
+        }
+    }
+
+    fn next_coord(&self, grid: &Grid<Tile>) -> Option<Pos> {
+        self.pos.step_in(self.direction, grid)
     }
+}
 
-    fn next_coord(&self) -> (usize, usize) {
-        let next = self.pos + self.direction;
-        (next.re as usize, next.im as usize)
+#[derive(Eq, PartialEq, Copy, Clone, Debug)]
+pub enum Tile {
+    Open,
+    Obstruction,
+    Guard,
+}
+
+impl TryFrom<char> for Tile {
+    type Error = char;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '.' => Ok(Self::Open),
+            '#' => Ok(Self::Obstruction),
+            '^' => Ok(Self::Guard),
+            c => Err(c),
+        }
     }
 }
 
 #[aoc_generator(day06)]
-pub fn generate(s: &str) -> Option<Matrix<char>> {
-    let v = s.lines().map(|l| l.chars().collect_vec()).collect_vec();
-    Matrix::from_rows(v).ok()
+pub fn generate(s: &str) -> Result<Grid<Tile>, ParseError> {
+    let src = Source::new(6, s);
+    let mut guards = s.match_indices('^');
+
+    if guards.next().is_none() {
+        return Err(src.end_of_input("expected a guard `^`"));
+    }
+    if let Some((idx, guard)) = guards.next() {
+        return Err(src.error(&s[idx..idx + guard.len()], "expected a single guard"));
+    }
+
+    grid::parse(&src, s)
 }
 
-fn walk_path(mut guard: Guard, grid: &Matrix<char>) -> Matrix<bool> {
-    let mut seen = Matrix::new(grid.rows, grid.columns, false);
-    seen[(guard.pos.re as usize, guard.pos.im as usize)] = true;
+fn render(step: usize, guard: Guard, grid: &Grid<Tile>, seen: &BitGrid) -> Frame {
+    let mut frame = Frame::from_grid(format!("step {step}"), grid, |tile| match tile {
+        Tile::Obstruction => '#',
+        Tile::Open | Tile::Guard => '.',
+    });
 
-    while let Some(c) = grid.get(guard.next_coord()) {
-        if *c == '.' {
-            guard.move_forward();
-            seen[(guard.pos.re as usize, guard.pos.im as usize)] = true;
+    for pos in seen.iter() {
+        frame.grid[pos] = 'X';
+    }
+    frame.grid[guard.pos] = guard.direction.arrow();
+
+    frame
+}
+
+/// The tiles visited until the guard leaves the map, or `None` if it walks in a loop.
+fn walk_path(mut guard: Guard, grid: &Grid<Tile>, sink: &mut dyn FrameSink) -> Option<BitGrid> {
+    let mut seen = BitGrid::new(grid.rows, grid.columns);
+    seen.insert(guard.pos);
+
+    let mut states = DirBitGrid::new(grid.rows, grid.columns);
+    let mut step = 0;
+    while let Some(next) = guard.next_coord(grid) {
+        if !states.insert(guard.pos, guard.direction) {
+            return None;
+        }
+
+        if sink.enabled() {
+            sink.emit(&render(step, guard, grid, &seen));
+        }
+        step += 1;
+
+        if grid[next] == Tile::Open {
+            guard.move_forward(grid);
+            seen.insert(guard.pos);
             continue;
         }
 
-        if *c == '#' {
+        if grid[next] == Tile::Obstruction {
             guard.turn_right();
         }
     }
 
-    seen
+    if sink.enabled() {
+        sink.emit(&render(step, guard, grid, &seen));
+    }
+
+    Some(seen)
 }
 
-#[aoc(day06, part1)]
-#[allow(clippy::cast_possible_wrap)]
-pub fn part1(inp: &Matrix<char>) -> usize {
+fn run_part1(inp: &Grid<Tile>, sink: &mut dyn FrameSink) -> Option<usize> {
     let mut grid = inp.clone();
 
-    let (start_row, start_col) = grid
-        .keys()
-        .find(|&(r, c)| grid[(r, c)] == '^')
-        .expect("start_row, start_col");
+    let start = grid.find(&Tile::Guard)?;
 
-    grid[(start_row, start_col)] = '.';
+    grid[start] = Tile::Open;
 
-    let guard = Guard {
-        pos: Complex::new(start_row as i64, start_col as i64),
-        direction: Complex::new(-1, 0),
-    };
+    let guard = Guard::new(start);
 
-    let path = walk_path(guard, &grid);
-    path.values().filter(|&v| *v).count()
+    let path = walk_path(guard, &grid, sink)?;
+    Some(path.count())
 }
 
-#[aoc(day06, part2)]
-#[allow(clippy::cast_possible_wrap)]
-pub fn part2(inp: &Matrix<char>) -> usize {
+#[aoc(day06, part1)]
+pub fn part1(inp: &Grid<Tile>) -> Option<usize> {
+    run_part1(inp, &mut NoViz)
+}
+
+fn run_part2(inp: &Grid<Tile>, sink: &mut dyn FrameSink) -> Option<usize> {
     let mut grid = inp.clone();
 
-    let (start_row, start_col) = grid
-        .keys()
-        .find(|&(r, c)| grid[(r, c)] == '^')
-        .expect("start_row, start_col");
+    let start = grid.find(&Tile::Guard)?;
 
-    grid[(start_row, start_col)] = '.';
+    grid[start] = Tile::Open;
 
-    let mut num_loops = 0;
+    let start_guard = Guard::new(start);
 
-    let start_guard = Guard {
-        pos: Complex::new(start_row as i64, start_col as i64),
-        direction: Complex::new(-1, 0),
-    };
+    let mut real_path = walk_path(start_guard, &grid, sink)?;
+    real_path.remove(start);
 
-    let real_path = walk_path(start_guard, &grid);
+    let candidates = real_path.iter().collect_vec();
+    let chunks = candidates.chunks(CHUNK).collect_vec();
 
-    for (r, c) in iproduct!(0..inp.rows, 0..inp.columns) {
-        if grid[(r, c)] == '#' || !real_path[(r, c)] || (r == start_row && c == start_col) {
-            continue;
+    Some(par::sum(chunks, |chunk| {
+        let mut seen = DirBitGrid::new(grid.rows, grid.columns);
+        chunk
+            .iter()
+            .filter(|&&pos| walks_in_loop(start_guard, pos, &grid, &mut seen))
+            .count()
+    }))
+}
+
+/// Whether the guard walks in a loop once an obstruction is added at `extra`,
+/// using `seen` for the states it has been in.
+fn walks_in_loop(mut guard: Guard, extra: Pos, grid: &Grid<Tile>, seen: &mut DirBitGrid) -> bool {
+    seen.clear();
+    while let Some(next) = guard.next_coord(grid) {
+        if !seen.insert(guard.pos, guard.direction) {
+            return true;
         }
 
-        grid[(r, c)] = '#';
+        if next == extra || grid[next] == Tile::Obstruction {
+            guard.turn_right();
+        } else if grid[next] == Tile::Open {
+            guard.move_forward(grid);
+        }
+    }
+
+    false
+}
 
-        let mut guard = start_guard;
+#[aoc(day06, part2)]
+pub fn part2(inp: &Grid<Tile>) -> Option<usize> {
+    run_part2(inp, &mut NoViz)
+}
 
-        let mut seen = HashSet::new();
-        while let Some(chr) = grid.get(guard.next_coord()) {
-            if !seen.insert(guard) {
-                num_loops += 1;
-                break;
-            }
+pub struct Day06;
 
-            match *chr {
-                '#' => guard.turn_right(),
-                '.' => guard.move_forward(),
-                _ => {}
-            };
-        }
+impl Solver for Day06 {
+    const DAY: u8 = 6;
 
-        grid[(r, c)] = '.';
+    type Input = Grid<Tile>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
     }
 
-    num_loops
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn visualize(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &PuzzleParams,
+        sink: &mut dyn FrameSink,
+    ) -> Result<Answer, Error> {
+        Ok(match part {
+            Part::One => run_part1(input, sink),
+            Part::Two => run_part2(input, sink),
+        }
+        .into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
+    use crate::viz::Recorder;
 
     const TEST_INPUT: &str = "....#.....\n\
                               .........#\n\
@@ -140,13 +248,41 @@ mod tests {
     fn test_p1() {
         let gen = generate(TEST_INPUT).expect("valid input");
         let res = part1(&gen);
-        assert_eq!(res, 41);
+        assert_eq!(res, Some(41));
+    }
+
+    #[test]
+    fn test_loop() {
+        let gen = generate(".#..\n...#\n#^..\n..#.").expect("valid input");
+        assert_eq!(part1(&gen), None);
+
+        let err = generate("^.\n.^").err().map(|e| (e.line, e.column));
+        assert_eq!(err, Some((2, 2)));
+    }
+
+    #[test]
+    fn test_visualize() {
+        let gen = generate(TEST_INPUT).expect("valid input");
+        let mut recorder = Recorder::default();
+        let res = Day06.visualize(&gen, Part::One, &PuzzleParams::new(), &mut recorder);
+
+        assert_eq!(res, Ok(Answer::Int(41)));
+        assert_eq!(recorder.frames[0].grid[(6, 4)], '^');
+        let last = recorder.frames.last().expect("at least one frame");
+        assert_eq!(last.grid[(9, 7)], 'v');
+        assert_eq!(last.grid.values().filter(|&&c| c == 'X').count(), 40);
     }
 
     #[test]
     fn test_p2() {
         let gen = generate(TEST_INPUT).expect("valid input");
         let res = part2(&gen);
-        assert_eq!(res, 6);
+        assert_eq!(res, Some(6));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(6, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(41)));
     }
 }
@@ -1,3 +1,9 @@
+use crate::arith::{self, Int, Overflow};
+use crate::error::{Error, ParseError};
+use crate::par;
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 
@@ -14,15 +20,17 @@ pub enum Operator {
     Concat,
 }
 
-const fn concat_numbers(lhs: i64, rhs: i64) -> i64 {
-    lhs * 10i64.pow(rhs.ilog10() + 1) + rhs
+fn concat_numbers(lhs: i64, rhs: i64) -> Option<i64> {
+    let digits = rhs.checked_ilog10().map_or(1, |n| n + 1);
+    lhs.checked_mul(10i64.pow(digits))?.checked_add(rhs)
 }
 
 impl Operator {
-    const fn execute(self, lhs: i64, rhs: i64) -> i64 {
+    /// `None` on overflow, which can never reach a valid target.
+    fn execute(self, lhs: i64, rhs: i64) -> Option<i64> {
         match self {
-            Self::Plus => lhs + rhs,
-            Self::Mul => lhs * rhs,
+            Self::Plus => lhs.checked_add(rhs),
+            Self::Mul => lhs.checked_mul(rhs),
             Self::Concat => concat_numbers(lhs, rhs),
         }
     }
@@ -38,10 +46,12 @@ impl Equation {
             return false;
         }
 
-        let (next_op, rest) = operands.split_first().expect("non-empty operands");
+        let Some((next_op, rest)) = operands.split_first() else {
+            return false;
+        };
         available_ops.iter().any(|op| {
-            let next = op.execute(current, *next_op);
-            self.can_solve_impl(next, rest, available_ops)
+            op.execute(current, *next_op)
+                .is_some_and(|next| self.can_solve_impl(next, rest, available_ops))
         })
     }
 
@@ -52,49 +62,72 @@ impl Equation {
 }
 
 #[aoc_generator(day07)]
-pub fn generate(s: &str) -> Vec<Equation> {
+pub fn generate(s: &str) -> Result<Vec<Equation>, ParseError> {
+    let src = Source::new(7, s);
+
     s.lines()
         .map(|l| {
-            let mut spl = l.split(": ");
-
-            let target = spl
-                .next()
-                .and_then(|n| n.parse::<i64>().ok())
-                .expect("lhs: rhs1...");
-            let numbers = spl
-                .next()
-                .and_then(|n| {
-                    n.split_ascii_whitespace()
-                        .filter_map(|n| n.parse::<i64>().ok())
-                        .collect_vec()
-                        .into()
-                })
-                .expect("lhs: rhs1...");
-
-            Equation { target, numbers }
+            let (target, numbers) = src.split_once(l, ": ")?;
+
+            let target = src.number(target)?;
+            let numbers = numbers
+                .split_ascii_whitespace()
+                .map(|n| src.number::<u32>(n).map(i64::from))
+                .try_collect()?;
+
+            Ok(Equation { target, numbers })
         })
-        .collect_vec()
+        .collect()
+}
+
+fn calibration_result(inp: &[Equation], available_ops: &[Operator]) -> Result<Int, Overflow> {
+    let solved = par::map(inp, |eq| eq.can_solve(available_ops).then_some(eq.target));
+    arith::sum(solved.into_iter().flatten().map(arith::int))
 }
 
 #[aoc(day07, part1)]
-pub fn part1(inp: &[Equation]) -> i64 {
-    inp.iter().fold(0, |acc, eq| {
-        let can_solve = eq.can_solve(&[Operator::Plus, Operator::Mul]);
-        acc + if can_solve { eq.target } else { 0 }
-    })
+pub fn part1(inp: &[Equation]) -> Result<Int, Overflow> {
+    calibration_result(inp, &[Operator::Plus, Operator::Mul])
 }
 
 #[aoc(day07, part2)]
-pub fn part2(inp: &[Equation]) -> i64 {
-    inp.iter().fold(0, |acc, eq| {
-        let can_solve = eq.can_solve(&[Operator::Plus, Operator::Mul, Operator::Concat]);
-        acc + if can_solve { eq.target } else { 0 }
-    })
+pub fn part2(inp: &[Equation]) -> Result<Int, Overflow> {
+    calibration_result(inp, &[Operator::Plus, Operator::Mul, Operator::Concat])
+}
+
+pub struct Day07;
+
+impl Solver for Day07 {
+    const DAY: u8 = 7;
+
+    type Input = Vec<Equation>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).ok().into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).ok().into()
+    }
+
+    fn part1_with(&self, input: &Self::Input, _params: &PuzzleParams) -> Result<Answer, Error> {
+        arith::answer(Self::DAY, part1(input))
+    }
+
+    fn part2_with(&self, input: &Self::Input, _params: &PuzzleParams) -> Result<Answer, Error> {
+        arith::answer(Self::DAY, part2(input))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "190: 10 19\n\
                               3267: 81 40 27\n\
@@ -108,15 +141,35 @@ mod tests {
 
     #[test]
     fn test_p1() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part1(&gen);
-        assert_eq!(res, 3749);
+        assert_eq!(res, arith::int(3749));
     }
 
     #[test]
     fn test_p2() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part2(&gen);
-        assert_eq!(res, 11387);
+        assert_eq!(res, arith::int(11387));
+    }
+
+    #[test]
+    #[cfg(any(feature = "checked", feature = "bigint"))]
+    fn test_overflow() {
+        // two equations solved by 2^31 * 2^31, which sum to 2^63
+        let input = "4611686018427387904: 2147483648 2147483648\n".repeat(2);
+        let res = solver::solve(7, Part::One, &input);
+
+        if cfg!(feature = "bigint") {
+            assert_eq!(res, Ok(Answer::Int(1 << 63)));
+        } else {
+            assert_eq!(res, Err(Error::Overflow { day: 7 }));
+        }
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(7, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(3749)));
     }
 }
@@ -1,24 +1,28 @@
+use crate::bitgrid::BitGrid;
+use crate::error::{Error, ParseError};
+use crate::grid::{self, Grid, GridExt};
+use crate::parse::Source;
+use crate::pos::{IPos, Pos};
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use pathfinding::prelude::Matrix;
 use std::collections::HashMap;
 
 pub struct Input {
-    grid: Matrix<char>,
-    coord_mapping: HashMap<char, Vec<(usize, usize)>>,
+    grid: Grid<char>,
+    coord_mapping: HashMap<char, Vec<Pos>>,
 }
 
 #[aoc_generator(day08)]
-pub fn generate(s: &str) -> Option<Input> {
-    let v = s.lines().map(|l| l.chars().collect_vec()).collect_vec();
-    let grid = Matrix::from_rows(v).ok()?;
+pub fn generate(s: &str) -> Result<Input, ParseError> {
+    let grid = grid::parse(&Source::new(8, s), s)?;
     let coord_mapping = grid
-        .keys()
+        .positions()
         .filter(|&pos| grid[pos] != '.')
         .map(|pos| (grid[pos], pos))
         .into_group_map();
 
-    Some(Input {
+    Ok(Input {
         grid,
         coord_mapping,
     })
@@ -26,67 +30,80 @@ pub fn generate(s: &str) -> Option<Input> {
 
 #[aoc(day08, part1)]
 pub fn part1(inp: &Input) -> usize {
-    let mut anti_nodes = Matrix::new(inp.grid.rows, inp.grid.columns, false);
+    let mut anti_nodes = BitGrid::new(inp.grid.rows, inp.grid.columns);
 
-    let mut check_pos = |(lr, lc): (usize, usize), (rr, rc): (usize, usize)| {
-        if let (Some(new_r), Some(new_c)) = ((2 * lr).checked_sub(rr), (2 * lc).checked_sub(rc)) {
-            if let Some(n) = anti_nodes.get_mut((new_r, new_c)) {
-                *n = true;
-            }
+    let mut check_pos = |l: Pos, r: Pos| {
+        if let Some(anti_node) = inp.grid.offset(l, IPos::from(l) - IPos::from(r)) {
+            anti_nodes.insert(anti_node);
         }
     };
 
     for pos in inp.coord_mapping.values() {
-        for &(sr, sc) in pos {
-            for &(tr, tc) in pos {
-                if sr == tr && sc == tc {
+        for &s in pos {
+            for &t in pos {
+                if s == t {
                     continue;
                 }
 
-                check_pos((sr, sc), (tr, tc));
-                check_pos((tr, tc), (sr, sc));
+                check_pos(s, t);
+                check_pos(t, s);
             }
         }
     }
 
-    anti_nodes.values().filter(|it| **it).count()
+    anti_nodes.count()
 }
 
 #[aoc(day08, part2)]
-#[allow(clippy::cast_possible_wrap)]
 pub fn part2(inp: &Input) -> usize {
-    let mut anti_nodes = Matrix::new(inp.grid.rows, inp.grid.columns, false);
+    let mut anti_nodes = BitGrid::new(inp.grid.rows, inp.grid.columns);
 
     for pos in inp.coord_mapping.values() {
-        for &(sr, sc) in pos {
-            for &(tr, tc) in pos {
-                if sr == tr && sc == tc {
+        for &s in pos {
+            for &t in pos {
+                if s == t {
                     continue;
                 }
 
-                anti_nodes[(sr, sc)] = true;
-                anti_nodes[(tr, tc)] = true;
-
-                let row_dist = sr as isize - tr as isize;
-                let col_dist = sc as isize - tc as isize;
+                anti_nodes.insert(s);
+                anti_nodes.insert(t);
 
-                anti_nodes
-                    .in_direction((sr, sc), (row_dist, col_dist))
-                    .for_each(|pos| anti_nodes[pos] = true);
+                let dist = IPos::from(s) - IPos::from(t);
 
-                anti_nodes
-                    .in_direction((tr, tc), (-row_dist, -col_dist))
-                    .for_each(|pos| anti_nodes[pos] = true);
+                anti_nodes.extend(inp.grid.ray(s, dist));
+                anti_nodes.extend(inp.grid.ray(t, -dist));
             }
         }
     }
 
-    anti_nodes.values().filter(|it| **it).count()
+    anti_nodes.count()
+}
+
+pub struct Day08;
+
+impl Solver for Day08 {
+    const DAY: u8 = 8;
+
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "............\n\
                               ........0...\n\
@@ -114,4 +131,10 @@ mod tests {
         let res = part2(&gen);
         assert_eq!(res, 34);
     }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(8, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(14)));
+    }
 }
@@ -1,19 +1,90 @@
-use aoc_runner_derive::aoc;
-use itertools::Itertools;
+use crate::arith::{self, Int, Overflow};
+use crate::error::{Error, ParseError};
+use crate::params::PuzzleParams;
+use crate::parse;
+use crate::solver::{Answer, Part, Solver, Variant};
+use aoc_runner_derive::{aoc, aoc_generator};
 use std::collections::HashMap;
+use std::io::BufRead;
 
-fn checksum(s: &[Option<usize>]) -> usize {
-    s.iter()
-        .enumerate()
-        .filter_map(|(idx, num)| num.map(|num| (idx, num)))
-        .fold(0, |acc, (idx, num)| acc + idx * num)
+/// The disk map as digit values, one byte per block length.
+#[aoc_generator(day09)]
+pub fn generate(s: &str) -> Result<Vec<u8>, ParseError> {
+    parse::from_text(9, s, generate_from)
+}
+
+/// Reads the disk map in chunks, so that a huge map is never held as text.
+pub fn generate_from<R: BufRead>(mut reader: R) -> Result<Vec<u8>, Error> {
+    parse::skip_bom(&mut reader)?;
+
+    let mut map = vec![];
+    let (mut line, mut column) = (1, 0);
+    // Whitespace is only allowed at the end of the input.
+    let mut space = None;
+    loop {
+        let buf = reader.fill_buf().map_err(|e| parse::io_error(&e))?;
+        if buf.is_empty() {
+            return Ok(map);
+        }
+
+        for &b in buf {
+            column += 1;
+            let error = if b.is_ascii_digit() {
+                match space {
+                    None => {
+                        map.push(b - b'0');
+                        continue;
+                    }
+                    Some(space) => space,
+                }
+            } else if b.is_ascii_whitespace() {
+                space = space.or(Some((line, column, b)));
+                if b == b'\n' {
+                    (line, column) = (line + 1, 0);
+                }
+                continue;
+            } else {
+                space.unwrap_or((line, column, b))
+            };
+
+            let (line, column, b) = error;
+            return Err(ParseError {
+                day: 9,
+                line,
+                column,
+                text: String::from_utf8_lossy(&[b]).into_owned(),
+                message: "expected a digit".to_string(),
+                at_end: false,
+            }
+            .into());
+        }
+
+        let len = buf.len();
+        reader.consume(len);
+    }
+}
+
+fn block_checksum(pos: usize, id: usize) -> Result<Int, Overflow> {
+    arith::mul(&arith::int(pos)?, &arith::int(id)?)
+}
+
+fn checksum(s: &[Option<usize>]) -> Result<Int, Overflow> {
+    arith::sum(
+        s.iter()
+            .enumerate()
+            .filter_map(|(idx, num)| num.map(|num| block_checksum(idx, num))),
+    )
 }
 
 fn reorder_blocks(v: &[Option<usize>]) -> Vec<Option<usize>> {
     let mut res = v.to_vec();
 
-    let mut last_non_empty = res.iter().rposition(Option::is_some).expect("non empty");
-    let mut first_empty = res.iter().position(Option::is_none).expect("has empty");
+    let (Some(mut last_non_empty), Some(mut first_empty)) = (
+        res.iter().rposition(Option::is_some),
+        res.iter().position(Option::is_none),
+    ) else {
+        return res;
+    };
 
     loop {
         if first_empty > last_non_empty {
@@ -37,43 +108,83 @@ fn reorder_blocks(v: &[Option<usize>]) -> Vec<Option<usize>> {
     res
 }
 
-fn expand_format(s: &str) -> Vec<Option<usize>> {
-    let mut res = vec![];
-
-    for (id, mut chnk) in s.chars().chunks(2).into_iter().enumerate() {
-        if let Some(file) = chnk.next().and_then(|it| it.to_digit(10)) {
-            res.extend_from_slice(&vec![Some(id); file as usize]);
-        }
+fn expand_format(map: &[u8]) -> Vec<Option<usize>> {
+    let mut res = Vec::with_capacity(map.iter().map(|&len| usize::from(len)).sum());
 
-        if let Some(free) = chnk.next().and_then(|it| it.to_digit(10)) {
-            res.extend_from_slice(&vec![None; free as usize]);
+    for (id, chnk) in map.chunks(2).enumerate() {
+        res.extend(std::iter::repeat_n(Some(id), usize::from(chnk[0])));
+        if let Some(&free) = chnk.get(1) {
+            res.extend(std::iter::repeat_n(None, usize::from(free)));
         }
     }
 
     res
 }
 
-#[aoc(day09, part1)]
-pub fn part1(inp: &str) -> usize {
+/// Part 1 block by block, as the puzzle describes it.
+pub fn part1_expanded(inp: &[u8]) -> Result<Int, Overflow> {
     let vec = expand_format(inp);
     let res = reorder_blocks(&vec);
 
     checksum(&res)
 }
 
+/// Part 1 without expanding the disk map: each gap is filled straight from the
+/// last file that still has blocks to move.
+#[aoc(day09, part1)]
+pub fn part1(inp: &[u8]) -> Result<Int, Overflow> {
+    let mut files = inp
+        .iter()
+        .step_by(2)
+        .map(|&len| usize::from(len))
+        .collect::<Vec<_>>();
+    let gaps = inp.iter().skip(1).step_by(2).map(|&len| usize::from(len));
+
+    let mut res = Int::default();
+    let mut span = |id: usize, start: usize, len: usize| -> Result<(), Overflow> {
+        let checksum = arith::sum((start..start + len).map(|pos| block_checksum(pos, id)))?;
+        res = arith::add(&res, &checksum)?;
+        Ok(())
+    };
+
+    let (mut pos, mut end) = (0, files.len());
+    for (id, gap) in (0..).zip(gaps.chain(std::iter::once(0))) {
+        if id >= end {
+            break;
+        }
+        span(id, pos, files[id])?;
+        pos += files[id];
+
+        let mut gap = gap;
+        while gap > 0 && end > id + 1 {
+            let last = end - 1;
+            let moved = gap.min(files[last]);
+            span(last, pos, moved)?;
+            (pos, gap) = (pos + moved, gap - moved);
+
+            files[last] -= moved;
+            if files[last] == 0 {
+                end -= 1;
+            }
+        }
+    }
+
+    Ok(res)
+}
+
 #[aoc(day09, part2)]
-pub fn part2(inp: &str) -> usize {
+pub fn part2(inp: &[u8]) -> Result<Int, Overflow> {
     let mut files = HashMap::new();
     let mut empty_space = Vec::new();
 
     let mut idx = 0;
-    for (id, mut chnk) in inp.chars().chunks(2).into_iter().enumerate() {
-        if let Some(file) = chnk.next().and_then(|it| it.to_digit(10)) {
-            files.insert(id, (idx, file));
-            idx += file;
-        }
+    for (id, chnk) in inp.chunks(2).enumerate() {
+        let file = usize::from(chnk[0]);
+        files.insert(id, (idx, file));
+        idx += file;
 
-        if let Some(free) = chnk.next().and_then(|it| it.to_digit(10)) {
+        if let Some(&free) = chnk.get(1) {
+            let free = usize::from(free);
             empty_space.push((idx, free));
             idx += free;
         }
@@ -98,26 +209,139 @@ pub fn part2(inp: &str) -> usize {
         }
     }
 
-    files.iter().fold(0, |acc, (k, &(idx, len))| {
-        (idx..idx + len).fold(acc, |acc, n| acc + k * n as usize)
-    })
+    arith::sum(
+        files.iter().flat_map(|(&id, &(idx, len))| {
+            (idx..idx + len).map(move |pos| block_checksum(pos, id))
+        }),
+    )
+}
+
+pub struct Day09;
+
+impl Solver for Day09 {
+    const DAY: u8 = 9;
+
+    type Input = Vec<u8>;
+
+    const VARIANTS: &'static [Variant<Self::Input>] = &[Variant {
+        name: "expanded",
+        part: Part::One,
+        approximate: false,
+        solve: |input, _| arith::answer(Self::DAY, part1_expanded(input)),
+    }];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).ok().into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).ok().into()
+    }
+
+    fn part1_with(&self, input: &Self::Input, _params: &PuzzleParams) -> Result<Answer, Error> {
+        arith::answer(Self::DAY, part1(input))
+    }
+
+    fn part2_with(&self, input: &Self::Input, _params: &PuzzleParams) -> Result<Answer, Error> {
+        arith::answer(Self::DAY, part2(input))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::solver::{self, Part};
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = "2333133121414131402";
 
     #[test]
     fn test_p1() {
-        let res = part1(TEST_INPUT);
-        assert_eq!(res, 1928);
+        let gen = generate(TEST_INPUT).expect("failed to parse input");
+        let res = part1(&gen);
+        assert_eq!(res, arith::int(1928));
+        assert_eq!(part1_expanded(&gen), res);
     }
 
     #[test]
     fn test_p2() {
-        let res = part2(TEST_INPUT);
-        assert_eq!(res, 2858);
+        let gen = generate(TEST_INPUT).expect("failed to parse input");
+        let res = part2(&gen);
+        assert_eq!(res, arith::int(2858));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let res = Day09.parse("12a45");
+        assert!(matches!(res, Err(Error::Parse(e)) if (e.line, e.column) == (1, 3)));
+
+        let err = generate("12\n45").expect_err("invalid input");
+        assert_eq!((err.line, err.column, err.text.as_str()), (1, 3, "\n"));
+    }
+
+    #[test]
+    fn test_stream() {
+        // A reader with a tiny buffer, so the map arrives in many chunks.
+        let input = parse::windows(TEST_INPUT);
+        let reader = std::io::BufReader::with_capacity(4, input.as_bytes());
+        let map = generate_from(reader).expect("failed to parse input");
+        assert_eq!(map, generate(TEST_INPUT).expect("failed to parse input"));
+    }
+
+    /// Moves whole files block by block, as the puzzle describes it.
+    fn reference_part2(inp: &[u8]) -> Result<Int, Overflow> {
+        let mut blocks = expand_format(inp);
+        let files = inp.len().div_ceil(2);
+
+        for id in (0..files).rev() {
+            let Some(start) = blocks.iter().position(|&b| b == Some(id)) else {
+                continue;
+            };
+            let len = blocks.iter().filter(|&&b| b == Some(id)).count();
+
+            let free = blocks
+                .windows(len)
+                .position(|w| w.iter().all(Option::is_none));
+            if let Some(free) = free.filter(|&free| free < start) {
+                for i in 0..len {
+                    blocks.swap(free + i, start + i);
+                }
+            }
+        }
+
+        checksum(&blocks)
+    }
+
+    fn disk_map() -> impl Strategy<Value = Vec<u8>> {
+        prop::collection::vec((1..=9u8, 0..=9u8), 1..12).prop_map(|files| {
+            let mut map = files
+                .iter()
+                .flat_map(|&pair| <[u8; 2]>::from(pair))
+                .collect::<Vec<_>>();
+            map.pop();
+            map
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_p2_reference(map in disk_map()) {
+            prop_assert_eq!(part2(&map), reference_part2(&map));
+        }
+
+        #[test]
+        fn test_p1_expanded(map in disk_map()) {
+            prop_assert_eq!(part1(&map), part1_expanded(&map));
+        }
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(9, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(1928)));
     }
 }
@@ -1,55 +1,69 @@
+use crate::dot::Graph;
+use crate::error::{Error, ParseError};
+use crate::grid::{self, Grid, GridExt};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::pos::Pos;
+use crate::search;
+use crate::solver::{Answer, Part, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use pathfinding::matrix::Matrix;
+use pathfinding::prelude::bfs_reach;
+use std::collections::HashSet;
 
 #[derive(Clone)]
 pub struct Input {
-    grid: Matrix<usize>,
+    grid: Grid<usize>,
     start_end_nodes: StartEndPoints,
 }
 
 #[derive(Clone)]
 pub struct StartEndPoints {
-    starts: Vec<(usize, usize)>,
-    ends: Vec<(usize, usize)>,
+    starts: Vec<Pos>,
+    ends: Vec<Pos>,
 }
 
 #[aoc_generator(day10)]
-pub fn generate(s: &str) -> Option<Input> {
-    let v = s
-        .lines()
-        .map(|l| {
-            l.chars()
-                .map(|c| c.to_digit(10).expect("valid digit") as usize)
-                .collect_vec()
-        })
-        .collect_vec();
-    let grid = Matrix::from_rows(v).ok()?;
-
-    let starts = grid.keys().filter(|&pos| grid[pos] == 0).collect_vec();
-    let ends = grid.keys().filter(|&pos| grid[pos] == 9).collect_vec();
-    Some(Input {
+pub fn generate(s: &str) -> Result<Input, ParseError> {
+    let grid = grid::parse_with(&Source::new(10, s), s, |c| {
+        c.to_digit(10).map(|h| h as usize)
+    })?;
+
+    let starts = grid.find_all(&0).collect_vec();
+    let ends = grid.find_all(&9).collect_vec();
+    Ok(Input {
         grid,
         start_end_nodes: StartEndPoints { starts, ends },
     })
 }
 
-fn successors((r, c): (usize, usize), grid: &Matrix<usize>) -> Vec<(usize, usize)> {
-    let mut succs = vec![];
+fn successors(pos: Pos, grid: &Grid<usize>) -> Vec<Pos> {
+    let next_height = grid[pos] + 1;
 
-    let next_height = grid[(r, c)] + 1;
+    grid.neighbors4(pos)
+        .filter(|&next| grid[next] == next_height)
+        .collect()
+}
 
-    for (dr, dc) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
-        if let (Some(new_r), Some(new_c)) = (r.checked_add_signed(dr), c.checked_add_signed(dc)) {
-            if let Some(node) = grid.get((new_r, new_c)) {
-                if *node == next_height {
-                    succs.push((new_r, new_c));
-                }
-            }
-        }
-    }
+fn predecessors(pos: Pos, grid: &Grid<usize>) -> Vec<Pos> {
+    grid.neighbors4(pos)
+        .filter(|&prev| grid[prev] + 1 == grid[pos])
+        .collect()
+}
 
-    succs
+/// Positions on some trail from a trailhead to a summit.
+fn on_trails(inp: &Input) -> HashSet<Pos> {
+    let StartEndPoints { starts, ends } = &inp.start_end_nodes;
+
+    let reachable = starts
+        .iter()
+        .flat_map(|&start| bfs_reach(start, |&pos| successors(pos, &inp.grid)))
+        .collect::<HashSet<_>>();
+
+    ends.iter()
+        .flat_map(|&end| bfs_reach(end, |&pos| predecessors(pos, &inp.grid)))
+        .filter(|pos| reachable.contains(pos))
+        .collect()
 }
 
 #[aoc(day10, part1)]
@@ -57,7 +71,7 @@ pub fn part1(inp: &Input) -> usize {
     let StartEndPoints { starts, ends } = &inp.start_end_nodes;
 
     starts.iter().fold(0, |acc, &start| {
-        acc + pathfinding::prelude::dijkstra_all(&start, |&pos| {
+        acc + search::dijkstra_all(&start, |&pos| {
             successors(pos, &inp.grid)
                 .into_iter()
                 .zip(std::iter::repeat(1))
@@ -74,7 +88,7 @@ pub fn part2(inp: &Input) -> usize {
     let StartEndPoints { starts, ends } = &inp.start_end_nodes;
 
     starts.iter().fold(0, |acc, &start| {
-        acc + pathfinding::prelude::count_paths(
+        acc + search::count_paths(
             start,
             |&pos| successors(pos, &inp.grid),
             |goal| ends.contains(goal),
@@ -82,9 +96,55 @@ pub fn part2(inp: &Input) -> usize {
     })
 }
 
+pub struct Day10;
+
+impl Solver for Day10 {
+    const DAY: u8 = 10;
+
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    /// Every step up by one, highlighting the positions both parts count
+    /// trails through.
+    fn graph(
+        &self,
+        input: &Self::Input,
+        _part: Part,
+        _params: &PuzzleParams,
+    ) -> Result<Graph, Error> {
+        let id = |pos: Pos| format!("{},{}", pos.row, pos.col);
+
+        let mut graph = Graph::directed();
+        for pos in input.grid.positions() {
+            graph.node(id(pos), input.grid[pos]);
+            for next in successors(pos, &input.grid) {
+                graph.edge(id(pos), id(next));
+            }
+        }
+        for pos in on_trails(input) {
+            graph.highlight_node(id(pos));
+        }
+
+        Ok(graph)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "89010123\n\
                               78121874\n\
@@ -108,4 +168,10 @@ mod tests {
         let res = part2(&gen);
         assert_eq!(res, 81);
     }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(10, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(36)));
+    }
 }
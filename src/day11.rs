@@ -1,65 +1,215 @@
+use crate::arith::{self, Int, Overflow};
+use crate::budget::Budget;
+use crate::cache::{self, Cache, DayCache};
+use crate::error::{Error, ParseError};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
-use memoize::memoize;
 use num::Integer;
 
 #[aoc_generator(day11)]
-pub fn generate(s: &str) -> Vec<usize> {
-    s.split_ascii_whitespace()
-        .map(|s| s.parse::<usize>().expect("number"))
-        .collect_vec()
+pub fn generate(s: &str) -> Result<Vec<usize>, ParseError> {
+    let src = Source::new(11, s);
+    s.split_ascii_whitespace().map(|n| src.number(n)).collect()
 }
 
-#[memoize]
-fn transform_digit(stone: usize, max_steps: usize) -> usize {
-    if max_steps == 0 {
-        return 1;
+/// Counts stones, remembering the count for every stone and number of blinks.
+///
+/// The counts do not depend on the input, so one counter can serve many.
+#[derive(Clone, Debug, Default)]
+pub struct StoneCounter {
+    cache: Cache<(usize, usize), Result<Int, Overflow>>,
+}
+
+impl StoneCounter {
+    /// The number of stones after `blinks` blinks.
+    ///
+    /// Engraved numbers that do not fit a `usize` are always an overflow.
+    pub fn count(&mut self, stones: &[usize], blinks: usize) -> Result<Int, Overflow> {
+        arith::sum(
+            stones
+                .iter()
+                .map(|&stone| self.transform_digit(stone, blinks)),
+        )
     }
 
-    if stone == 0 {
-        return transform_digit(1, max_steps - 1);
+    // `Int` is only `Copy` without the `bigint` feature.
+    #[cfg_attr(not(feature = "bigint"), allow(clippy::clone_on_copy))]
+    fn transform_digit(&mut self, stone: usize, max_steps: usize) -> Result<Int, Overflow> {
+        if let Some(count) = self.cache.get(&(stone, max_steps)) {
+            return count.clone();
+        }
+
+        let count = self.transform_digit_uncached(stone, max_steps);
+        self.cache.insert((stone, max_steps), count.clone());
+        count
     }
 
-    let num_digits = 1 + stone.ilog10();
-    if num_digits.is_even() {
-        let divisor = 10usize.pow(num_digits / 2);
+    fn transform_digit_uncached(
+        &mut self,
+        stone: usize,
+        max_steps: usize,
+    ) -> Result<Int, Overflow> {
+        if max_steps == 0 {
+            return Ok(Int::from(1));
+        }
+
+        if stone == 0 {
+            return self.transform_digit(1, max_steps - 1);
+        }
+
+        let num_digits = 1 + stone.ilog10();
+        if num_digits.is_even() {
+            let divisor = 10usize.pow(num_digits / 2);
+
+            return arith::add(
+                &self.transform_digit(stone / divisor, max_steps - 1)?,
+                &self.transform_digit(stone % divisor, max_steps - 1)?,
+            );
+        }
 
-        return transform_digit(stone / divisor, max_steps - 1)
-            + transform_digit(stone % divisor, max_steps - 1);
+        let stone = stone.checked_mul(2024).ok_or(Overflow)?;
+        self.transform_digit(stone, max_steps - 1)
+    }
+}
+
+impl DayCache for StoneCounter {
+    fn cache_size(&self) -> usize {
+        self.cache.len()
     }
 
-    transform_digit(stone * 2024, max_steps - 1)
+    fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+}
+
+const BLINKS_P1: usize = 25;
+const BLINKS_P2: usize = 75;
+/// The most `blinks`, each of which is a level of recursion.
+const MAX_BLINKS: usize = 1000;
+
+fn count_stones(inp: &[usize], blinks: usize) -> Result<Int, Overflow> {
+    StoneCounter::default().count(inp, blinks)
 }
 
 #[aoc(day11, part1)]
-pub fn part1(inp: &[usize]) -> usize {
-    inp.iter()
-        .fold(0usize, |acc, stone| acc + transform_digit(*stone, 25))
+pub fn part1(inp: &[usize]) -> Result<Int, Overflow> {
+    count_stones(inp, BLINKS_P1)
 }
 
 #[aoc(day11, part2)]
-pub fn part2(inp: &[usize]) -> usize {
-    inp.iter()
-        .fold(0usize, |acc, stone| acc + transform_digit(*stone, 75))
+pub fn part2(inp: &[usize]) -> Result<Int, Overflow> {
+    count_stones(inp, BLINKS_P2)
+}
+
+pub struct Day11;
+
+impl Solver for Day11 {
+    const DAY: u8 = 11;
+    const PARAMS: &'static [&'static str] = &["blinks"];
+
+    type Input = Vec<usize>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).ok().into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).ok().into()
+    }
+
+    fn part1_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        let mut counter = StoneCounter::default();
+        self.part1_cached(input, params, &Budget::unlimited(), &mut counter)
+    }
+
+    fn part2_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        let mut counter = StoneCounter::default();
+        self.part2_cached(input, params, &Budget::unlimited(), &mut counter)
+    }
+
+    fn new_cache(&self) -> Option<Box<dyn DayCache>> {
+        Some(Box::<StoneCounter>::default())
+    }
+
+    fn part1_cached(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        _budget: &Budget,
+        cache: &mut dyn DayCache,
+    ) -> Result<Answer, Error> {
+        let blinks = params.get_in("blinks", BLINKS_P1, 0..=MAX_BLINKS)?;
+        let counter = cache::downcast::<StoneCounter>(Self::DAY, cache)?;
+        arith::answer(Self::DAY, counter.count(input, blinks))
+    }
+
+    fn part2_cached(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        _budget: &Budget,
+        cache: &mut dyn DayCache,
+    ) -> Result<Answer, Error> {
+        let blinks = params.get_in("blinks", BLINKS_P2, 0..=MAX_BLINKS)?;
+        let counter = cache::downcast::<StoneCounter>(Self::DAY, cache)?;
+        arith::answer(Self::DAY, counter.count(input, blinks))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "125 17";
 
     #[test]
     fn test_p1() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part1(&gen);
-        assert_eq!(res, 55312);
+        assert_eq!(res, arith::int(55312));
     }
 
     #[test]
     fn test_p2() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part2(&gen);
-        assert_eq!(res, 65_601_038_650_482);
+        assert_eq!(res, arith::int(65_601_038_650_482u64));
+    }
+
+    #[test]
+    fn test_reuse() {
+        let mut counter = StoneCounter::default();
+        assert_eq!(counter.count(&[125, 17], 6), arith::int(22));
+
+        let size = counter.cache_size();
+        assert_eq!(counter.count(&[125], 6), arith::int(7));
+        assert_eq!(counter.cache_size(), size);
+
+        counter.clear_cache();
+        assert_eq!(counter.cache_size(), 0);
+    }
+
+    #[test]
+    fn test_max_blinks() {
+        let with = |blinks| PuzzleParams::new().with("blinks", blinks);
+        let res = solver::solve_with(11, Part::One, "0", &with(MAX_BLINKS));
+        assert!(matches!(res, Ok(_) | Err(Error::Overflow { .. })));
+
+        let res = solver::solve_with(11, Part::One, "0", &with(MAX_BLINKS + 1));
+        assert!(matches!(res, Err(Error::InvalidParam { .. })));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(11, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(55312)));
     }
 }
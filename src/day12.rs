@@ -1,70 +1,62 @@
+use crate::bitgrid::BitGrid;
+use crate::error::{Error, ParseError};
+use crate::grid::{self, Grid, GridExt};
+use crate::parse::Source;
+use crate::pos::{Dir, IPos, Pos};
+use crate::search;
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use pathfinding::prelude::Matrix;
-use std::collections::HashSet;
 
 pub struct ConnectedComponent {
-    nodes: HashSet<(usize, usize)>,
+    nodes: BitGrid,
 }
 
 impl ConnectedComponent {
     fn area(&self) -> usize {
-        self.nodes.len()
+        self.nodes.count()
     }
 
-    #[allow(clippy::cast_possible_wrap)]
     fn perimeter(&self) -> usize {
-        self.nodes.iter().fold(0, |acc, &(r, c)| {
-            let mut num_succs = 0;
-            let r = r as isize;
-            let c = c as isize;
-            for (sr, sc) in [(r - 1, c), (r + 1, c), (r, c - 1), (r, c + 1)] {
-                if sr < 0 || sc < 0 {
-                    continue;
-                }
-
-                if self.nodes.contains(&(sr as usize, sc as usize)) {
-                    num_succs += 1;
-                }
-            }
+        self.nodes.iter().fold(0, |acc, pos| {
+            let num_succs = Dir::ALL
+                .into_iter()
+                .filter_map(|dir| pos.step(dir))
+                .filter(|&next| self.nodes.contains(next))
+                .count();
 
             acc + 4 - num_succs
         })
     }
 
-    #[allow(clippy::cast_possible_wrap)]
     fn count_corners(&self) -> usize {
         let mut result = 0;
 
-        let comp = self
-            .nodes
-            .iter()
-            .map(|&(r, c)| (r as isize, c as isize))
-            .collect::<HashSet<_>>();
-
-        for &(r, c) in &comp {
-            let above = (r - 1, c);
-            let below = (r + 1, c);
-            let left = (r, c - 1);
-            let right = (r, c + 1);
-            let top_right = (r - 1, c + 1);
-            let top_left = (r - 1, c - 1);
-            let bot_right = (r + 1, c + 1);
-            let bot_left = (r + 1, c - 1);
+        let contains = |pos: IPos| Pos::try_from(pos).is_ok_and(|pos| self.nodes.contains(pos));
+
+        for pos in self.nodes.iter().map(IPos::from) {
+            let above = pos.step(Dir::North);
+            let below = pos.step(Dir::South);
+            let left = pos.step(Dir::West);
+            let right = pos.step(Dir::East);
+            let top_right = above.step(Dir::East);
+            let top_left = above.step(Dir::West);
+            let bot_right = below.step(Dir::East);
+            let bot_left = below.step(Dir::West);
 
             // outside corners
 
             // above is empty and left is empty
-            let top_left_corner = !comp.contains(&above) && !comp.contains(&left);
+            let top_left_corner = !contains(above) && !contains(left);
 
             // above is empty and right is empty
-            let top_right_corner = !comp.contains(&above) && !comp.contains(&right);
+            let top_right_corner = !contains(above) && !contains(right);
 
             // left is empty and below is empty
-            let bot_left_corner = !comp.contains(&left) && !comp.contains(&below);
+            let bot_left_corner = !contains(left) && !contains(below);
 
             // right is empty and below is empty
-            let bot_right_corner = !comp.contains(&right) && !comp.contains(&below);
+            let bot_right_corner = !contains(right) && !contains(below);
 
             result += usize::from(top_left_corner)
                 + usize::from(top_right_corner)
@@ -74,20 +66,16 @@ impl ConnectedComponent {
             // inside corners
 
             // below contained, right contained, bot-right diag not contained
-            let top_left_inside =
-                comp.contains(&below) && comp.contains(&right) && !comp.contains(&bot_right);
+            let top_left_inside = contains(below) && contains(right) && !contains(bot_right);
 
             // below contained, left contained, bot-left diag not contained
-            let top_right_inside =
-                comp.contains(&below) && comp.contains(&left) && !comp.contains(&bot_left);
+            let top_right_inside = contains(below) && contains(left) && !contains(bot_left);
 
             // above contained, right contained, top-right diag not contained
-            let bot_left_inside =
-                comp.contains(&above) && comp.contains(&right) && !comp.contains(&top_right);
+            let bot_left_inside = contains(above) && contains(right) && !contains(top_right);
 
             // above contained, left contained, top-left diag not contained
-            let bot_right_inside =
-                comp.contains(&above) && comp.contains(&left) && !comp.contains(&top_left);
+            let bot_right_inside = contains(above) && contains(left) && !contains(top_left);
 
             result += usize::from(top_left_inside)
                 + usize::from(top_right_inside)
@@ -100,37 +88,30 @@ impl ConnectedComponent {
 }
 
 #[aoc_generator(day12)]
-pub fn generate(s: &str) -> Option<Vec<ConnectedComponent>> {
-    let v = s.lines().map(|l| l.chars().collect_vec()).collect_vec();
-    let grid = Matrix::from_rows(v).ok()?;
+pub fn generate(s: &str) -> Result<Vec<ConnectedComponent>, ParseError> {
+    let grid = grid::parse(&Source::new(12, s), s)?;
 
     let connected_components = get_components(&grid)
         .iter()
-        .map(|comp| ConnectedComponent {
-            nodes: comp.iter().copied().collect::<HashSet<_, _>>(),
+        .map(|comp| {
+            let mut nodes = BitGrid::new(grid.rows, grid.columns);
+            nodes.extend(comp.iter().copied());
+            ConnectedComponent { nodes }
         })
         .collect_vec();
 
-    Some(connected_components)
+    Ok(connected_components)
 }
 
-fn succs(grid: &Matrix<char>, (r, c): (usize, usize)) -> Vec<(usize, usize)> {
-    let mut succs = vec![];
-
-    for (dr, dc) in [(0, 1), (-1, 0), (0, -1), (1, 0)] {
-        if let (Some(new_r), Some(new_c)) = (r.checked_add_signed(dr), c.checked_add_signed(dc)) {
-            if grid.get((new_r, new_c)) == grid.get((r, c)) {
-                succs.push((new_r, new_c));
-            }
-        }
-    }
-
-    succs
+fn succs(grid: &Grid<char>, pos: Pos) -> Vec<Pos> {
+    grid.neighbors4(pos)
+        .filter(|&next| grid[next] == grid[pos])
+        .collect()
 }
 
-fn get_components(grid: &Matrix<char>) -> Vec<Vec<(usize, usize)>> {
-    let nodes = grid.keys().collect_vec();
-    pathfinding::prelude::strongly_connected_components(&nodes, |&(r, c)| succs(grid, (r, c)))
+fn get_components(grid: &Grid<char>) -> Vec<Vec<Pos>> {
+    let nodes = grid.positions().collect_vec();
+    search::strongly_connected_components(&nodes, |&pos| succs(grid, pos))
 }
 
 #[aoc(day12, part1)]
@@ -145,9 +126,31 @@ pub fn part2(inp: &[ConnectedComponent]) -> usize {
         .fold(0, |acc, comp| acc + comp.area() * comp.count_corners())
 }
 
+pub struct Day12;
+
+impl Solver for Day12 {
+    const DAY: u8 = 12;
+
+    type Input = Vec<ConnectedComponent>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "RRRRIICCFF\n\
                               RRRRIICCCF\n\
@@ -173,4 +176,10 @@ mod tests {
         let res = part2(&gen);
         assert_eq!(res, 1206);
     }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(12, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(1930)));
+    }
 }
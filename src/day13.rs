@@ -1,23 +1,29 @@
+use crate::arith::{self, Int, Overflow};
+use crate::error::{Error, ParseError};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use parse_display_derive::{Display, FromStr};
 
-const BUTTON_A_COST: usize = 3;
-const BUTTON_B_COST: usize = 1;
+const BUTTON_A_COST: u8 = 3;
+const BUTTON_B_COST: u8 = 1;
+const PRIZE_OFFSET: i64 = 10_000_000_000_000;
 
 #[derive(Display, FromStr, Clone, Debug)]
 #[display("Button {name}: X+{x_offset}, Y+{y_offset}")]
 pub struct Button {
     name: String,
-    x_offset: f64,
-    y_offset: f64,
+    x_offset: Int,
+    y_offset: Int,
 }
 
-#[derive(Display, FromStr, Copy, Clone, Debug)]
+#[derive(Display, FromStr, Clone, Debug)]
 #[display("Prize: X={x}, Y={y}")]
 pub struct Prize {
-    x: f64,
-    y: f64,
+    x: Int,
+    y: Int,
 }
 
 #[derive(Clone, Debug)]
@@ -27,35 +33,31 @@ pub struct InputData {
     prize: Prize,
 }
 
-#[aoc_generator(day13, part1)]
-pub fn generate_p1(s: &str) -> Vec<InputData> {
-    s.split("\n\n")
-        .filter_map(|s| {
-            let mut lines = s.lines();
+#[aoc_generator(day13)]
+pub fn generate(s: &str) -> Result<Vec<InputData>, ParseError> {
+    const BUTTON_FORMAT: &str = "expected `Button <name>: X+<n>, Y+<n>`";
 
-            let button_a = lines.next()?.parse::<Button>().ok()?;
-            let button_b = lines.next()?.parse::<Button>().ok()?;
-            let prize = lines.next()?.parse::<Prize>().ok()?;
+    let src = Source::new(13, s);
 
-            Some(InputData {
-                button_a,
-                button_b,
-                prize,
+    s.split("\n\n")
+        .map(|block| {
+            let [button_a, button_b, prize] = block.lines().collect_vec()[..] else {
+                return Err(src.error(block, "expected two buttons and a prize"));
+            };
+
+            Ok(InputData {
+                button_a: button_a
+                    .parse()
+                    .map_err(|_| src.error(button_a, BUTTON_FORMAT))?,
+                button_b: button_b
+                    .parse()
+                    .map_err(|_| src.error(button_b, BUTTON_FORMAT))?,
+                prize: prize
+                    .parse()
+                    .map_err(|_| src.error(prize, "expected `Prize: X=<n>, Y=<n>`"))?,
             })
         })
-        .collect_vec()
-}
-
-#[aoc_generator(day13, part2)]
-pub fn generate_p2(s: &str) -> Vec<InputData> {
-    let mut parsed = generate_p1(s);
-
-    for input in &mut parsed {
-        input.prize.x += 10_000_000_000_000.0;
-        input.prize.y += 10_000_000_000_000.0;
-    }
-
-    parsed
+        .collect()
 }
 
 // solve linear equations
@@ -85,8 +87,9 @@ pub fn generate_p2(s: &str) -> Vec<InputData> {
 // divide by bx
 // j = (px - ax * i) / bx
 
-fn calculate_num_presses(input: &InputData) -> (usize, usize) {
-    let Prize { x: px, y: py } = input.prize;
+/// Zero presses of both buttons if the prize cannot be won.
+fn calculate_num_presses(input: &InputData, prize: &Prize) -> Result<(Int, Int), Overflow> {
+    let Prize { x: px, y: py } = prize;
 
     let Button {
         x_offset: ax,
@@ -100,38 +103,90 @@ fn calculate_num_presses(input: &InputData) -> (usize, usize) {
         ..
     } = &input.button_b;
 
+    let none = || (Int::default(), Int::default());
+
     // i = (px * by - py * bx) / (ax * by - ay * bx)
-    let a_presses = (px * by - py * bx) / (ax * by - ay * bx);
+    let num = arith::sub(&arith::mul(px, by)?, &arith::mul(py, bx)?)?;
+    let den = arith::sub(&arith::mul(ax, by)?, &arith::mul(ay, bx)?)?;
 
-    if a_presses.fract() != 0.0 {
-        return (0, 0);
-    }
+    // the prize is only won by pressing each button a whole number of times
+    let Some(a_presses) = arith::div_exact(&num, &den) else {
+        return Ok(none());
+    };
 
     // j = (px - ax * i) / bx
-    let b_presses = (px - ax * a_presses) / bx;
+    let rest = arith::sub(px, &arith::mul(ax, &a_presses)?)?;
+    let Some(b_presses) = arith::div_exact(&rest, bx) else {
+        return Ok(none());
+    };
 
-    (a_presses as usize, b_presses as usize)
+    if a_presses < Int::default() || b_presses < Int::default() {
+        return Ok(none());
+    }
+
+    Ok((a_presses, b_presses))
+}
+
+fn total_cost(inp: &[InputData], offset: i64) -> Result<Int, Overflow> {
+    let offset = Int::from(offset);
+    arith::sum(inp.iter().map(|input| {
+        let prize = Prize {
+            x: arith::add(&input.prize.x, &offset)?,
+            y: arith::add(&input.prize.y, &offset)?,
+        };
+        let (a_presses, b_presses) = calculate_num_presses(input, &prize)?;
+        arith::add(
+            &arith::mul(&a_presses, &Int::from(BUTTON_A_COST))?,
+            &arith::mul(&b_presses, &Int::from(BUTTON_B_COST))?,
+        )
+    }))
 }
 
 #[aoc(day13, part1)]
-pub fn part1(inp: &[InputData]) -> usize {
-    inp.iter().fold(0usize, |acc, input| {
-        let (a_presses, b_presses) = calculate_num_presses(input);
-        acc + a_presses * BUTTON_A_COST + b_presses * BUTTON_B_COST
-    })
+pub fn part1(inp: &[InputData]) -> Result<Int, Overflow> {
+    total_cost(inp, 0)
 }
 
+/// Like part 1, with every prize moved by [`PRIZE_OFFSET`] on both axes.
 #[aoc(day13, part2)]
-pub fn part2(inp: &[InputData]) -> usize {
-    inp.iter().fold(0usize, |acc, input| {
-        let (a_presses, b_presses) = calculate_num_presses(input);
-        acc + a_presses * BUTTON_A_COST + b_presses * BUTTON_B_COST
-    })
+pub fn part2(inp: &[InputData]) -> Result<Int, Overflow> {
+    total_cost(inp, PRIZE_OFFSET)
+}
+
+pub struct Day13;
+
+impl Solver for Day13 {
+    const DAY: u8 = 13;
+
+    type Input = Vec<InputData>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).ok().into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).ok().into()
+    }
+
+    fn part1_with(&self, input: &Self::Input, _params: &PuzzleParams) -> Result<Answer, Error> {
+        arith::answer(Self::DAY, part1(input))
+    }
+
+    fn part2_with(&self, input: &Self::Input, _params: &PuzzleParams) -> Result<Answer, Error> {
+        arith::answer(Self::DAY, part2(input))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = "Button A: X+94, Y+34\n\
                               Button B: X+22, Y+67\n\
@@ -151,8 +206,71 @@ mod tests {
 
     #[test]
     fn test_p1() {
-        let gen = generate_p1(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part1(&gen);
-        assert_eq!(res, 480);
+        assert_eq!(res, arith::int(480));
+    }
+
+    #[test]
+    fn test_p2() {
+        let gen = generate(TEST_INPUT).expect("valid input");
+        let res = part2(&gen);
+        assert_eq!(res, arith::int(875_318_608_908u64));
+    }
+
+    /// Tries every number of presses of button A.
+    fn reference_cost((ax, ay): (u32, u32), (bx, by): (u32, u32), (px, py): (u32, u32)) -> u32 {
+        (0..=px / ax)
+            .filter_map(|a| {
+                let rem = px - a * ax;
+                let b = rem / bx;
+                (rem % bx == 0 && a * ay + b * by == py)
+                    .then_some(a * u32::from(BUTTON_A_COST) + b * u32::from(BUTTON_B_COST))
+            })
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn machine(a: (u32, u32), b: (u32, u32), prize: (u32, u32)) -> InputData {
+        let button = |name: &str, (x, y): (u32, u32)| Button {
+            name: name.to_string(),
+            x_offset: Int::from(x),
+            y_offset: Int::from(y),
+        };
+
+        InputData {
+            button_a: button("A", a),
+            button_b: button("B", b),
+            prize: Prize {
+                x: Int::from(prize.0),
+                y: Int::from(prize.1),
+            },
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_presses_reference(
+            a in (1..30u32, 1..30u32),
+            b in (1..30u32, 1..30u32),
+            presses in (0..30u32, 0..30u32),
+            offset in (0..3u32, 0..3u32),
+        ) {
+            // like in the real input, the buttons never move in the same direction
+            prop_assume!(a.0 * b.1 != a.1 * b.0);
+
+            let axis = |a, b, offset| presses.0 * a + presses.1 * b + offset;
+            let prize = (axis(a.0, b.0, offset.0), axis(a.1, b.1, offset.1));
+            prop_assert_eq!(
+                total_cost(&[machine(a, b, prize)], 0),
+                arith::int(reference_cost(a, b, prize))
+            );
+        }
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(13, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(480)));
     }
 }
@@ -1,5 +1,10 @@
+use crate::budget::{self, Budget, Exhausted};
+use crate::error::{Error, ParseError};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::solver::{Answer, Part, Solver};
+use crate::viz::{Frame, FrameSink, NoViz};
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::Itertools;
 use parse_display_derive::{Display, FromStr};
 use pathfinding::prelude::Matrix;
 
@@ -13,8 +18,28 @@ pub struct Entry {
 }
 
 #[aoc_generator(day14)]
-pub fn generate(s: &str) -> Vec<Entry> {
-    s.lines().filter_map(|line| line.parse().ok()).collect_vec()
+pub fn generate(s: &str) -> Result<Vec<Entry>, ParseError> {
+    let src = Source::new(14, s);
+    s.lines()
+        .map(|line| {
+            line.parse()
+                .map_err(|_| src.error(line, "expected `p=<x>,<y> v=<dx>,<dy>`"))
+        })
+        .collect()
+}
+
+/// Robots teleport across the edges, so only positions and velocities modulo
+/// the grid size matter; reducing them keeps every position on the grid.
+fn wrap(entries: &[Entry], width: isize, height: isize) -> Vec<Entry> {
+    entries
+        .iter()
+        .map(|e| Entry {
+            px: e.px.rem_euclid(width),
+            py: e.py.rem_euclid(height),
+            vx: e.vx.rem_euclid(width),
+            vy: e.vy.rem_euclid(height),
+        })
+        .collect()
 }
 
 fn simulate(entries: &mut [Entry], width: isize, height: isize) {
@@ -34,11 +59,41 @@ fn entries_to_grid(entries: &[Entry], width: usize, height: usize) -> Matrix<usi
     grid
 }
 
-fn run_part1(entries: &mut [Entry], width: isize, height: isize) -> usize {
-    for _ in 0..100 {
+fn render(second: usize, entries: &[Entry], width: isize, height: isize) -> Frame {
+    let grid = entries_to_grid(entries, width as usize, height as usize);
+    Frame::from_grid(format!("second {second}"), &grid, |&n| match n {
+        0 => '.',
+        1..=9 => char::from_digit(n as u32, 10).unwrap_or('+'),
+        _ => '+',
+    })
+}
+
+const WIDTH: isize = 101;
+const HEIGHT: isize = 103;
+/// The largest `width` and `height`, which size the grid of each frame.
+const MAX_SIZE: isize = 1000;
+const SECONDS: usize = 100;
+
+fn run_part1(
+    entries: &mut [Entry],
+    width: isize,
+    height: isize,
+    seconds: usize,
+    sink: &mut dyn FrameSink,
+    budget: &Budget,
+) -> Result<usize, Exhausted> {
+    for second in 0..seconds {
+        budget.step()?;
+        if sink.enabled() {
+            sink.emit(&render(second, entries, width, height));
+        }
         simulate(entries, width, height);
     }
 
+    if sink.enabled() {
+        sink.emit(&render(seconds, entries, width, height));
+    }
+
     let rows = height as usize / 2;
     let cols = width as usize / 2;
 
@@ -60,16 +115,20 @@ fn run_part1(entries: &mut [Entry], width: isize, height: isize) -> usize {
         }
     }
 
-    top_left * top_right * bot_left * bot_right
+    Ok(top_left * top_right * bot_left * bot_right)
 }
 
 #[aoc(day14, part1)]
-pub fn part1(inp: &[Entry]) -> usize {
-    const WIDTH: isize = 101;
-    const HEIGHT: isize = 103;
-
-    let mut entries = inp.to_vec();
-    run_part1(&mut entries, WIDTH, HEIGHT)
+pub fn part1(inp: &[Entry]) -> Result<usize, Exhausted> {
+    let mut entries = wrap(inp, WIDTH, HEIGHT);
+    run_part1(
+        &mut entries,
+        WIDTH,
+        HEIGHT,
+        SECONDS,
+        &mut NoViz,
+        &Budget::unlimited(),
+    )
 }
 
 // stupid heuristic checking >7 values > 0 in a row/column
@@ -96,28 +155,131 @@ fn has_tree(grid: &Matrix<usize>) -> bool {
     false
 }
 
-#[aoc(day14, part2)]
-pub fn part2(inp: &[Entry]) -> usize {
-    const WIDTH: isize = 101;
-    const HEIGHT: isize = 103;
+/// Searches until the robots are back where they started, which happens
+/// after at most `width * height` seconds.
+fn run_part2(
+    entries: &mut [Entry],
+    width: isize,
+    height: isize,
+    sink: &mut dyn FrameSink,
+    budget: &Budget,
+) -> Result<Option<usize>, Exhausted> {
+    let period = (width as usize).saturating_mul(height as usize);
 
-    let mut entries = inp.to_vec();
+    for idx in 1..=period {
+        budget.step()?;
+        simulate(entries, width, height);
 
-    let mut idx = 0;
-    loop {
-        idx += 1;
-        simulate(&mut entries, WIDTH, HEIGHT);
+        if sink.enabled() {
+            sink.emit(&render(idx, entries, width, height));
+        }
 
-        let grid = entries_to_grid(&entries, WIDTH as usize, HEIGHT as usize);
+        let grid = entries_to_grid(entries, width as usize, height as usize);
         if has_tree(&grid) {
-            return idx;
+            return Ok(Some(idx));
         }
     }
+
+    Ok(None)
+}
+
+#[aoc(day14, part2)]
+pub fn part2(inp: &[Entry]) -> Option<usize> {
+    let mut entries = wrap(inp, WIDTH, HEIGHT);
+    run_part2(
+        &mut entries,
+        WIDTH,
+        HEIGHT,
+        &mut NoViz,
+        &Budget::unlimited(),
+    )
+    .ok()
+    .flatten()
+}
+
+fn solve(
+    input: &[Entry],
+    part: Part,
+    params: &PuzzleParams,
+    sink: &mut dyn FrameSink,
+    budget: &Budget,
+) -> Result<Answer, Error> {
+    let width = params.get_in("width", WIDTH, 1..=MAX_SIZE)?;
+    let height = params.get_in("height", HEIGHT, 1..=MAX_SIZE)?;
+    let mut entries = wrap(input, width, height);
+
+    let answer = match part {
+        Part::One => {
+            let seconds = params.get("seconds", SECONDS)?;
+            run_part1(&mut entries, width, height, seconds, sink, budget).map(Answer::from)
+        }
+        Part::Two => run_part2(&mut entries, width, height, sink, budget).map(Answer::from),
+    };
+    budget::answer(Day14::DAY, answer)
+}
+
+pub struct Day14;
+
+impl Solver for Day14 {
+    const DAY: u8 = 14;
+    const PARAMS: &'static [&'static str] = &["width", "height", "seconds"];
+
+    type Input = Vec<Entry>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).ok().into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn part1_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        self.part1_within(input, params, &Budget::unlimited())
+    }
+
+    fn part2_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        self.part2_within(input, params, &Budget::unlimited())
+    }
+
+    fn part1_within(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        budget: &Budget,
+    ) -> Result<Answer, Error> {
+        solve(input, Part::One, params, &mut NoViz, budget)
+    }
+
+    fn part2_within(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        budget: &Budget,
+    ) -> Result<Answer, Error> {
+        solve(input, Part::Two, params, &mut NoViz, budget)
+    }
+
+    fn visualize(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        params: &PuzzleParams,
+        sink: &mut dyn FrameSink,
+    ) -> Result<Answer, Error> {
+        solve(input, part, params, sink, &Budget::unlimited())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "p=0,4 v=3,-3\n\
                               p=6,3 v=-1,-3\n\
@@ -134,8 +296,34 @@ mod tests {
 
     #[test]
     fn test_p1() {
-        let mut gen = generate(TEST_INPUT);
-        let res = run_part1(&mut gen, 11, 7);
-        assert_eq!(res, 12);
+        let gen = generate(TEST_INPUT).expect("valid input");
+        let params = PuzzleParams::new().with("width", 11).with("height", 7);
+        let res = Day14.part1_with(&gen, &params);
+        assert_eq!(res, Ok(Answer::Int(12)));
+    }
+
+    #[test]
+    fn test_budget() {
+        let gen = generate(TEST_INPUT).expect("valid input");
+        let params = PuzzleParams::new().with("seconds", usize::MAX);
+        let res = Day14.part1_within(&gen, &params, &Budget::with_max_steps(1000));
+        assert_eq!(
+            res,
+            Err(Error::GaveUp {
+                day: 14,
+                reason: Exhausted::Steps(1000)
+            })
+        );
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve_with(
+            14,
+            Part::One,
+            &parse::windows(TEST_INPUT),
+            &PuzzleParams::new().with("width", 11).with("height", 7),
+        );
+        assert_eq!(res, Ok(Answer::Int(12)));
     }
 }
@@ -1,285 +1,351 @@
+use crate::error::{Error, ParseError};
+use crate::grid::{self, Grid, GridExt};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::pos::{Dir, Pos};
+use crate::solver::{Answer, Part, Solver};
+use crate::viz::{Frame, FrameSink, NoViz};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use pathfinding::prelude::Matrix;
 
 #[derive(Clone, Debug)]
 pub struct Input {
-    grid: Matrix<char>,
-    insts: Vec<char>,
+    grid: Grid<char>,
+    insts: Vec<Dir>,
+}
+
+fn parse_moves(src: &Source<'_>, movements: &str) -> Result<Vec<Dir>, ParseError> {
+    let mut insts = vec![];
+
+    for line in movements.lines() {
+        for (idx, c) in line.char_indices() {
+            let dir = Dir::try_from(c)
+                .map_err(|_| src.error(&line[idx..idx + c.len_utf8()], "expected one of `<>^v`"))?;
+
+            insts.push(dir);
+        }
+    }
+
+    Ok(insts)
+}
+
+/// Checks that there is a single robot and that the map is walled in.
+fn check_map(src: &Source<'_>, map: &str) -> Result<(), ParseError> {
+    let mut robots = map.match_indices('@');
+    if robots.next().is_none() {
+        return Err(src.error(map, "expected a robot `@`"));
+    }
+    if let Some((idx, robot)) = robots.next() {
+        return Err(src.error(&map[idx..idx + robot.len()], "expected a single robot"));
+    }
+
+    let rows = map.lines().count();
+    for (row, line) in map.lines().enumerate() {
+        let columns = line.chars().count();
+        let open = line.char_indices().enumerate().find(|&(col, (_, c))| {
+            let border = row == 0 || row + 1 == rows || col == 0 || col + 1 == columns;
+            border && c != '#'
+        });
+
+        if let Some((_, (idx, c))) = open {
+            let span = &line[idx..idx + c.len_utf8()];
+            return Err(src.error(span, "expected the map to be surrounded by walls"));
+        }
+    }
+
+    Ok(())
 }
 
 #[aoc_generator(day15, part1)]
-pub fn generate_p1(s: &str) -> Option<Input> {
-    let (map, movements) = s.split_once("\n\n")?;
-    let v = map.lines().map(|l| l.chars().collect_vec()).collect_vec();
-    let grid = Matrix::from_rows(v).ok()?;
+pub fn generate_p1(s: &str) -> Result<Input, ParseError> {
+    let src = Source::new(15, s);
+    let (map, movements) = src.sections()?;
 
-    let insts = movements.lines().join("").chars().collect_vec();
+    let grid = grid::parse_with(&src, map, |c| {
+        matches!(c, '#' | '.' | 'O' | '@').then_some(c)
+    })?;
+    check_map(&src, map)?;
+    let insts = parse_moves(&src, movements)?;
 
-    Some(Input { grid, insts })
+    Ok(Input { grid, insts })
 }
 
 #[aoc_generator(day15, part2)]
-pub fn generate_p2(s: &str) -> Option<Input> {
-    let (map, movements) = s.split_once("\n\n")?;
-    let v = map
-        .lines()
-        .map(|l| {
-            l.chars()
-                .flat_map(|c| match c {
-                    'O' => ['[', ']'],
-                    '@' => ['@', '.'],
-                    '#' => ['#', '#'],
-                    '.' => ['.', '.'],
-                    _ => panic!("unexpected char: {c}"),
-                })
-                .collect_vec()
+pub fn generate_p2(s: &str) -> Result<Input, ParseError> {
+    let Input { grid, insts } = generate_p1(s)?;
+
+    let widened = grid
+        .values()
+        .flat_map(|&c| match c {
+            'O' => ['[', ']'],
+            '@' => ['@', '.'],
+            c => [c, c],
         })
         .collect_vec();
-    let grid = Matrix::from_rows(v).ok()?;
+    let grid = Grid::from_vec(grid.rows, 2 * grid.columns, widened)
+        .expect("every tile is widened to two columns");
 
-    let insts = movements.lines().join("").chars().collect_vec();
-
-    Some(Input { grid, insts })
+    Ok(Input { grid, insts })
 }
 
-fn find_start_pos(grid: &Matrix<char>) -> Option<(usize, usize)> {
-    grid.keys().find(|&(r, c)| grid[(r, c)] == '@')
+/// The tile next to `pos`, which always exists because the map is walled in.
+fn ahead(pos: Pos, dir: Dir) -> Pos {
+    pos.step(dir).expect("the map is surrounded by walls")
 }
 
-#[allow(clippy::cast_possible_wrap)]
-fn can_move_horizontally(start: (usize, usize), col_dir: isize, grid: &Matrix<char>) -> bool {
-    let (nr, nc) = (start.0, start.1 as isize + col_dir);
-    let (nr, nc) = (nr, nc as usize);
+fn can_move_horizontally(start: Pos, dir: Dir, grid: &Grid<char>) -> bool {
+    let next = ahead(start, dir);
 
     // simple case: wall
-    if grid[(nr, nc)] == '#' {
+    if grid[next] == '#' {
         return false;
     }
 
     // simple case: empty space
-    if grid[(nr, nc)] == '.' {
+    if grid[next] == '.' {
         return true;
     }
 
-    can_move_horizontally((nr, nc), col_dir, grid)
+    can_move_horizontally(next, dir, grid)
 }
 
-#[allow(clippy::cast_possible_wrap)]
-fn do_move_horizontally((br, bc): (usize, usize), col_dir: isize, grid: &mut Matrix<char>) {
-    assert_ne!(col_dir, 0);
+fn do_move_horizontally(start: Pos, dir: Dir, grid: &mut Grid<char>) {
+    assert!(!dir.is_vertical());
     // move boxes first
     let boxes = grid
-        .in_direction((br, bc), (0, col_dir))
+        .ray(start, dir.delta())
         .take_while(|&p| grid[p] == 'O' || grid[p] == '[' || grid[p] == ']')
         .collect_vec();
 
-    for &(br, bc) in boxes.iter().rev() {
-        let after_box = (br as isize, bc as isize + col_dir);
-        let after_box = (after_box.0 as usize, after_box.1 as usize);
+    for &b in boxes.iter().rev() {
+        let after_box = ahead(b, dir);
 
         assert_eq!(grid[after_box], '.');
-        grid.swap(after_box, (br, bc));
+        grid.swap(after_box.into(), b.into());
     }
 }
 
-#[allow(clippy::cast_possible_wrap)]
-fn can_move_vertically(
-    (br, bc): (usize, usize),
-    row_dir: isize,
-    is_part2: bool,
-    grid: &Matrix<char>,
-) -> bool {
-    assert_ne!(row_dir, 0);
+fn can_move_vertically(b: Pos, dir: Dir, is_part2: bool, grid: &Grid<char>) -> bool {
+    assert!(dir.is_vertical());
 
     if is_part2 {
-        assert_eq!(grid[(br, bc)], '[');
-        assert_eq!(grid[(br, bc + 1)], ']');
+        let right = ahead(b, Dir::East);
+        assert_eq!(grid[b], '[');
+        assert_eq!(grid[right], ']');
 
-        let next_br = (br as isize + row_dir) as usize;
-        if grid[(next_br, bc)] == '.' && grid[(next_br, bc + 1)] == '.' {
+        let (above_left, above_right) = (ahead(b, dir), ahead(right, dir));
+        if grid[above_left] == '.' && grid[above_right] == '.' {
             return true;
         }
 
-        if grid[(next_br, bc)] == '#' || grid[(next_br, bc + 1)] == '#' {
+        if grid[above_left] == '#' || grid[above_right] == '#' {
             return false;
         }
 
-        let above_bc_left = grid[(next_br, bc)];
-        let above_bc_right = grid[(next_br, bc + 1)];
-
-        if above_bc_left == '[' && above_bc_right == ']' {
-            return can_move_vertically((next_br, bc), row_dir, is_part2, grid);
+        if grid[above_left] == '[' && grid[above_right] == ']' {
+            return can_move_vertically(above_left, dir, is_part2, grid);
         }
 
-        if above_bc_left == ']' && !can_move_vertically((next_br, bc - 1), row_dir, is_part2, grid)
+        if grid[above_left] == ']'
+            && !can_move_vertically(ahead(above_left, Dir::West), dir, is_part2, grid)
         {
             return false;
         }
 
-        if above_bc_right == '[' && !can_move_vertically((next_br, bc + 1), row_dir, is_part2, grid)
-        {
+        if grid[above_right] == '[' && !can_move_vertically(above_right, dir, is_part2, grid) {
             return false;
         }
 
         true
     } else {
-        let (nr, nc) = (br as isize + row_dir, bc as isize);
-        let (nr, nc) = (nr as usize, nc as usize);
+        let next = ahead(b, dir);
 
         // simple case: wall
-        if grid[(nr, nc)] == '#' {
+        if grid[next] == '#' {
             return false;
         }
 
         // simple case: empty space
-        if grid[(nr, nc)] == '.' {
+        if grid[next] == '.' {
             return true;
         }
 
-        can_move_vertically((nr, nc), row_dir, is_part2, grid)
+        can_move_vertically(next, dir, is_part2, grid)
     }
 }
 
-#[allow(clippy::cast_possible_wrap)]
-fn do_move_vertically(
-    (br, bc): (usize, usize),
-    row_dir: isize,
-    is_part2: bool,
-    grid: &mut Matrix<char>,
-) {
-    assert_ne!(row_dir, 0);
+fn do_move_vertically(b: Pos, dir: Dir, is_part2: bool, grid: &mut Grid<char>) {
+    assert!(dir.is_vertical());
 
-    let next_br = (br as isize + row_dir) as usize;
+    let next = ahead(b, dir);
 
     if is_part2 {
-        assert_eq!(grid[(br, bc)], '[');
-        assert_eq!(grid[(br, bc + 1)], ']');
+        let right = ahead(b, Dir::East);
+        let next_right = ahead(right, dir);
+        assert_eq!(grid[b], '[');
+        assert_eq!(grid[right], ']');
 
         // []
         // []
-        if grid[(next_br, bc)] == '[' && grid[(next_br, bc + 1)] == ']' {
-            do_move_vertically((next_br, bc), row_dir, is_part2, grid);
+        if grid[next] == '[' && grid[next_right] == ']' {
+            do_move_vertically(next, dir, is_part2, grid);
         }
 
         // []
         // .[
-        if grid[(next_br, bc)] == ']' {
-            do_move_vertically((next_br, bc - 1), row_dir, is_part2, grid);
+        if grid[next] == ']' {
+            do_move_vertically(ahead(next, Dir::West), dir, is_part2, grid);
         }
 
         // .[
         // []
-        if grid[(next_br, bc + 1)] == '[' {
-            do_move_vertically((next_br, bc + 1), row_dir, is_part2, grid);
+        if grid[next_right] == '[' {
+            do_move_vertically(next_right, dir, is_part2, grid);
         }
 
         // ..
         // []
-        if grid[(next_br, bc)] == '.' && grid[(next_br, bc + 1)] == '.' {
-            grid.swap((br, bc), (next_br, bc));
-            grid.swap((br, bc + 1), (next_br, bc + 1));
+        if grid[next] == '.' && grid[next_right] == '.' {
+            grid.swap(b.into(), next.into());
+            grid.swap(right.into(), next_right.into());
         }
     } else {
-        if grid[(next_br, bc)] == 'O' {
-            do_move_vertically((next_br, bc), row_dir, is_part2, grid);
+        if grid[next] == 'O' {
+            do_move_vertically(next, dir, is_part2, grid);
         }
 
-        assert_eq!(grid[(next_br, bc)], '.');
-        grid.swap((br, bc), (next_br, bc));
+        assert_eq!(grid[next], '.');
+        grid.swap(b.into(), next.into());
     }
 }
 
-#[allow(clippy::cast_possible_wrap)]
-fn move_towards(
-    (dr, dc): (isize, isize),
-    (rr, rc): &mut (usize, usize),
-    is_part2: bool,
-    grid: &mut Matrix<char>,
-) {
-    let (nr, nc) = (*rr as isize + dr, *rc as isize + dc);
-    assert!(nr >= 0 && nc >= 0);
-    let (nr, nc) = (nr as usize, nc as usize);
+fn move_towards(dir: Dir, robot: &mut Pos, is_part2: bool, grid: &mut Grid<char>) {
+    let next = ahead(*robot, dir);
 
     // simple case: wall
-    if grid[(nr, nc)] == '#' {
+    if grid[next] == '#' {
         return;
     }
 
     // simple case: empty space
-    if grid[(nr, nc)] == '.' {
-        *rr = nr;
-        *rc = nc;
+    if grid[next] == '.' {
+        *robot = next;
         return;
     }
 
-    if dr == 0 {
-        if can_move_horizontally((*rr, *rc), dc, grid) {
-            do_move_horizontally((*rr, *rc), dc, grid);
-            *rc = (*rc as isize + dc) as usize;
+    if !dir.is_vertical() {
+        if can_move_horizontally(*robot, dir, grid) {
+            do_move_horizontally(*robot, dir, grid);
+            *robot = next;
         }
 
         return;
     }
 
-    let (start_row, start_col) = if is_part2 {
+    let start = if is_part2 {
         // moving up we need to check the neighbouring column as well
-        let is_left_edge = grid[(nr, nc)] == '[';
-        assert!(is_left_edge || grid[(nr, nc)] == ']');
+        let is_left_edge = grid[next] == '[';
+        assert!(is_left_edge || grid[next] == ']');
 
         // for p2 we assume the start position to be '['
         if is_left_edge {
-            (nr, nc)
+            next
         } else {
-            (nr, nc - 1)
+            ahead(next, Dir::West)
         }
     } else {
-        (*rr, *rc)
+        *robot
     };
 
-    if can_move_vertically((start_row, start_col), dr, is_part2, grid) {
-        do_move_vertically((start_row, start_col), dr, is_part2, grid);
-        *rr = (*rr as isize + dr) as usize;
+    if can_move_vertically(start, dir, is_part2, grid) {
+        do_move_vertically(start, dir, is_part2, grid);
+        *robot = next;
     }
 }
 
-fn run_instructions(inp: &Input, is_part2: bool) -> Option<usize> {
+fn render(caption: String, grid: &Grid<char>, robot: Pos) -> Frame {
+    let mut frame = Frame::new(caption, grid.clone());
+    frame.grid[robot] = '@';
+    frame
+}
+
+fn run_instructions(inp: &Input, is_part2: bool, sink: &mut dyn FrameSink) -> Option<usize> {
     let mut grid = inp.grid.clone();
 
-    let mut robot_pos = find_start_pos(&grid)?;
+    let mut robot_pos = grid.find(&'@')?;
     grid[robot_pos] = '.';
 
-    for inst in &inp.insts {
-        match *inst {
-            '>' => move_towards((0, 1), &mut robot_pos, is_part2, &mut grid),
-            '<' => move_towards((0, -1), &mut robot_pos, is_part2, &mut grid),
-            '^' => move_towards((-1, 0), &mut robot_pos, is_part2, &mut grid),
-            'v' => move_towards((1, 0), &mut robot_pos, is_part2, &mut grid),
-            _ => panic!("invalid instruction"),
-        };
+    if sink.enabled() {
+        sink.emit(&render("start".to_string(), &grid, robot_pos));
+    }
+
+    for (idx, &dir) in inp.insts.iter().enumerate() {
+        move_towards(dir, &mut robot_pos, is_part2, &mut grid);
+
+        if sink.enabled() {
+            let caption = format!("move {}: {}", idx + 1, dir.arrow());
+            sink.emit(&render(caption, &grid, robot_pos));
+        }
     }
 
     Some(
-        grid.keys()
+        grid.positions()
             .filter(|&pos| grid[pos] == if is_part2 { '[' } else { 'O' })
-            .map(|(r, c)| r * 100 + c)
+            .map(|pos| pos.row * 100 + pos.col)
             .sum::<usize>(),
     )
 }
 
 #[aoc(day15, part1)]
 pub fn part1(inp: &Input) -> Option<usize> {
-    run_instructions(inp, false)
+    run_instructions(inp, false, &mut NoViz)
 }
 
 #[aoc(day15, part2)]
 pub fn part2(inp: &Input) -> Option<usize> {
-    run_instructions(inp, true)
+    run_instructions(inp, true, &mut NoViz)
+}
+
+pub struct Day15;
+
+impl Solver for Day15 {
+    const DAY: u8 = 15;
+
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate_p1(input)?)
+    }
+
+    fn parse_part2(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate_p2(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn visualize(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &PuzzleParams,
+        sink: &mut dyn FrameSink,
+    ) -> Result<Answer, Error> {
+        Ok(run_instructions(input, part == Part::Two, sink).into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
+    use crate::viz::Recorder;
 
     const TEST_INPUT: &str = "########\n\
                               #..O.O.#\n\
@@ -321,6 +387,19 @@ mod tests {
         assert_eq!(res, Some(2028));
     }
 
+    #[test]
+    fn test_visualize() {
+        let gen = generate_p1(TEST_INPUT).expect("valid input");
+        let mut recorder = Recorder::default();
+        let res = Day15.visualize(&gen, Part::One, &PuzzleParams::new(), &mut recorder);
+
+        assert_eq!(res, Ok(Answer::Int(2028)));
+        assert_eq!(recorder.frames.len(), gen.insts.len() + 1);
+        // the first move is blocked by a wall
+        assert_eq!(recorder.frames[0].grid, recorder.frames[1].grid);
+        assert_eq!(recorder.frames[2].grid[(1, 2)], '@');
+    }
+
     #[test]
     fn test_p2() {
         let gen = generate_p2(TEST_INPUT_P2).expect("valid input");
@@ -328,6 +407,21 @@ mod tests {
         assert_eq!(res, Some(9021));
     }
 
+    #[test]
+    fn test_parse_error() {
+        let err = generate_p2("#####\n#@.x#\n#####\n\n<>").expect_err("invalid input");
+        assert_eq!((err.line, err.column, err.text.as_str()), (2, 4, "x"));
+
+        let err = generate_p1("#####\n#@..#\n#####\n\n<>\n^^w").expect_err("invalid input");
+        assert_eq!((err.line, err.column, err.text.as_str()), (6, 3, "w"));
+
+        let err = generate_p1("#####\n#@.@#\n#####\n\n<>").expect_err("two robots");
+        assert_eq!((err.line, err.column), (2, 4));
+
+        let err = generate_p1("#####\n#@...\n#####\n\n<>").expect_err("open map");
+        assert_eq!((err.line, err.column), (2, 5));
+    }
+
     #[test]
     fn test_can_move() {
         let txt = "..@...\n\
@@ -336,10 +430,9 @@ mod tests {
                         [][][]\n\
                         ......";
 
-        let v = txt.lines().map(|l| l.chars().collect_vec()).collect_vec();
-        let grid = Matrix::from_rows(v).expect("valid text");
+        let grid = grid::parse(&Source::new(15, txt), txt).expect("valid text");
 
-        assert!(can_move_vertically((1, 2), 1, true, &grid));
+        assert!(can_move_vertically(Pos::new(1, 2), Dir::South, true, &grid));
     }
 
     #[test]
@@ -348,10 +441,9 @@ mod tests {
                         .[]...\n\
                         ..[]..";
 
-        let v = txt.lines().map(|l| l.chars().collect_vec()).collect_vec();
-        let grid = Matrix::from_rows(v).expect("valid text");
+        let grid = grid::parse(&Source::new(15, txt), txt).expect("valid text");
 
-        assert!(can_move_vertically((2, 2), -1, true, &grid));
+        assert!(can_move_vertically(Pos::new(2, 2), Dir::North, true, &grid));
     }
 
     #[test]
@@ -365,10 +457,9 @@ mod tests {
                         ...[]..\n\
                         .......";
 
-        let v = txt.lines().map(|l| l.chars().collect_vec()).collect_vec();
-        let grid = Matrix::from_rows(v).expect("valid text");
+        let grid = grid::parse(&Source::new(15, txt), txt).expect("valid text");
 
-        assert!(can_move_vertically((6, 3), -1, true, &grid));
+        assert!(can_move_vertically(Pos::new(6, 3), Dir::North, true, &grid));
     }
 
     #[test]
@@ -381,10 +472,14 @@ mod tests {
                         ......\n\
                         ......";
 
-        let v = txt.lines().map(|l| l.chars().collect_vec()).collect_vec();
-        let grid = Matrix::from_rows(v).expect("valid text");
+        let grid = grid::parse(&Source::new(15, txt), txt).expect("valid text");
 
-        assert!(!can_move_vertically((1, 2), 1, true, &grid));
+        assert!(!can_move_vertically(
+            Pos::new(1, 2),
+            Dir::South,
+            true,
+            &grid
+        ));
     }
 
     #[test]
@@ -401,9 +496,19 @@ mod tests {
                         ##..........[][]##\n\
                         ##################";
 
-        let v = txt.lines().map(|l| l.chars().collect_vec()).collect_vec();
-        let grid = Matrix::from_rows(v).expect("valid text");
+        let grid = grid::parse(&Source::new(15, txt), txt).expect("valid text");
 
-        assert!(!can_move_vertically((8, 6), -1, true, &grid));
+        assert!(!can_move_vertically(
+            Pos::new(8, 6),
+            Dir::North,
+            true,
+            &grid
+        ));
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(15, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(2028)));
     }
 }
@@ -1,74 +1,52 @@
+use crate::dot::Graph;
+use crate::error::{Error, ParseError};
+use crate::grid::{self, Grid, GridExt, MazeTile};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::pos::{Dir, Pos};
+use crate::search;
+use crate::solver::{Answer, Part, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use pathfinding::prelude::Matrix;
 
 #[aoc_generator(day16)]
-pub fn generate(s: &str) -> Option<Matrix<char>> {
-    let v = s.lines().map(|l| l.chars().collect_vec()).collect_vec();
-    Matrix::from_rows(v).ok()
-}
-
-fn find_node(pos: char, grid: &Matrix<char>) -> Option<(usize, usize)> {
-    grid.keys().find(|&(r, c)| grid[(r, c)] == pos)
-}
-
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
+pub fn generate(s: &str) -> Result<Grid<MazeTile>, ParseError> {
+    grid::parse(&Source::new(16, s), s)
 }
 
 #[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
 struct State {
-    pos: (usize, usize),
-    dir: Direction,
+    pos: Pos,
+    dir: Dir,
 }
 
 impl State {
-    const fn with_dir(self, dir: Direction) -> Self {
+    fn id(self) -> String {
+        format!("{},{} {}", self.pos.row, self.pos.col, self.dir.arrow())
+    }
+
+    const fn with_dir(self, dir: Dir) -> Self {
         Self { dir, ..self }
     }
 
-    fn move_forward(self, grid: &Matrix<char>) -> Option<Self> {
-        let new_pos = match self.dir {
-            Direction::North => {
-                let new_r = self.pos.0.checked_add_signed(-1)?;
-                (new_r, self.pos.1)
-            }
-            Direction::East => (self.pos.0, self.pos.1 + 1),
-            Direction::South => (self.pos.0 + 1, self.pos.1),
-            Direction::West => {
-                let new_c = self.pos.1.checked_add_signed(-1)?;
-                (self.pos.0, new_c)
-            }
-        };
+    fn move_forward(self, grid: &Grid<MazeTile>) -> Option<Self> {
+        let new_pos = self.pos.step_in(self.dir, grid)?;
 
-        if grid.get(new_pos).is_some_and(|pos| *pos != '#') {
-            Some(Self {
-                pos: new_pos,
-                ..self
-            })
-        } else {
-            None
-        }
+        (grid[new_pos] != MazeTile::Wall).then_some(Self {
+            pos: new_pos,
+            ..self
+        })
     }
 }
 
 fn add_turns(state: State, states: &mut Vec<(State, usize)>) {
     const TURN_COST: usize = 1000;
 
-    if state.dir == Direction::North || state.dir == Direction::South {
-        states.push((state.with_dir(Direction::East), TURN_COST));
-        states.push((state.with_dir(Direction::West), TURN_COST));
-    } else if state.dir == Direction::East || state.dir == Direction::West {
-        states.push((state.with_dir(Direction::North), TURN_COST));
-        states.push((state.with_dir(Direction::South), TURN_COST));
-    }
+    states.push((state.with_dir(state.dir.turn_left()), TURN_COST));
+    states.push((state.with_dir(state.dir.turn_right()), TURN_COST));
 }
 
-fn successors(state: State, grid: &Matrix<char>) -> Vec<(State, usize)> {
+fn successors(state: State, grid: &Grid<MazeTile>) -> Vec<(State, usize)> {
     const MOVE_COST: usize = 1;
 
     let mut succs = vec![];
@@ -81,39 +59,43 @@ fn successors(state: State, grid: &Matrix<char>) -> Vec<(State, usize)> {
     succs
 }
 
-#[aoc(day16, part1)]
-#[allow(clippy::too_many_lines)]
-pub fn part1(grid: &Matrix<char>) -> Option<usize> {
-    let start_pos = find_node('S', grid)?;
-    let goal_pos = find_node('E', grid)?;
+/// The reindeer's starting state and the position it has to reach.
+fn start_and_goal(grid: &Grid<MazeTile>) -> Option<(State, Pos)> {
+    let start_pos = grid.find(&MazeTile::Start)?;
+    let goal_pos = grid.find(&MazeTile::End)?;
 
     let state = State {
         pos: start_pos,
-        dir: Direction::East,
+        dir: Dir::East,
     };
-
-    let (_, cost) =
-        pathfinding::prelude::dijkstra(&state, |s| successors(*s, grid), |s| s.pos == goal_pos)?;
-    Some(cost)
+    Some((state, goal_pos))
 }
 
-#[aoc(day16, part2)]
-pub fn part2(grid: &Matrix<char>) -> Option<usize> {
-    let start_pos = find_node('S', grid)?;
-    let goal_pos = find_node('E', grid)?;
-    let start_dir = Direction::East;
-
-    let state = State {
-        pos: start_pos,
-        dir: start_dir,
-    };
+fn best_path(grid: &Grid<MazeTile>) -> Option<(Vec<State>, usize)> {
+    let (state, goal_pos) = start_and_goal(grid)?;
+    search::dijkstra(&state, |s| successors(*s, grid), |s| s.pos == goal_pos)
+}
 
-    let (paths, _) = pathfinding::prelude::astar_bag_collect(
+fn best_paths(grid: &Grid<MazeTile>) -> Option<Vec<Vec<State>>> {
+    let (state, goal_pos) = start_and_goal(grid)?;
+    let (paths, _) = search::astar_bag_collect(
         &state,
         |s| successors(*s, grid),
         |_| 1, // dummy heuristic
         |s| s.pos == goal_pos,
     )?;
+    Some(paths)
+}
+
+#[aoc(day16, part1)]
+pub fn part1(grid: &Grid<MazeTile>) -> Option<usize> {
+    let (_, cost) = best_path(grid)?;
+    Some(cost)
+}
+
+#[aoc(day16, part2)]
+pub fn part2(grid: &Grid<MazeTile>) -> Option<usize> {
+    let paths = best_paths(grid)?;
 
     let num_unique_positions = paths
         .iter()
@@ -123,9 +105,65 @@ pub fn part2(grid: &Matrix<char>) -> Option<usize> {
     Some(num_unique_positions)
 }
 
+pub struct Day16;
+
+impl Solver for Day16 {
+    const DAY: u8 = 16;
+
+    type Input = Grid<MazeTile>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    /// The reindeer's states with the cost of each move, highlighting one
+    /// best path in part 1 and all of them in part 2.
+    fn graph(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &PuzzleParams,
+    ) -> Result<Graph, Error> {
+        let mut graph = Graph::directed();
+        for pos in input
+            .positions()
+            .filter(|&pos| input[pos] != MazeTile::Wall)
+        {
+            for dir in Dir::ALL {
+                let state = State { pos, dir };
+                for (next, cost) in successors(state, input) {
+                    graph.labeled_edge(state.id(), next.id(), cost);
+                }
+            }
+        }
+
+        let paths = match part {
+            Part::One => best_path(input).map(|(path, _)| vec![path]),
+            Part::Two => best_paths(input),
+        };
+        for path in paths.iter().flatten() {
+            for (from, to) in path.iter().tuple_windows() {
+                graph.highlight_edge(from.id(), to.id());
+            }
+        }
+
+        Ok(graph)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "###############\n\
                               #.......#....E#\n\
@@ -156,4 +194,10 @@ mod tests {
         let res = part2(&gen);
         assert_eq!(res, Some(45));
     }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(16, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(7036)));
+    }
 }
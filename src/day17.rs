@@ -1,6 +1,12 @@
+use crate::budget::{self, Budget, CancelToken, Exhausted, Limits};
+use crate::error::{Error, ParseError};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::solver::{Answer, Part, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use parse_display_derive::{Display, FromStr};
+use std::time::Duration;
 
 #[derive(Display, FromStr, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum Instruction {
@@ -38,40 +44,61 @@ pub struct Input {
     program: Vec<Instruction>,
 }
 
-fn parse_register(line: &str) -> Option<usize> {
-    line.split_once(": ")
-        .expect("valid input")
-        .1
-        .parse::<usize>()
-        .ok()
+fn parse_register(src: &Source<'_>, line: &str) -> Result<usize, ParseError> {
+    let (_, value) = src.split_once(line, ": ")?;
+    src.number(value)
 }
 
 #[aoc_generator(day17)]
-pub fn generate(s: &str) -> Option<Input> {
-    let (regs, program_txt) = s.split_once("\n\n")?;
+pub fn generate(s: &str) -> Result<Input, ParseError> {
+    let src = Source::new(17, s);
+    let (regs, program_txt) = src.sections()?;
+
+    let [reg_a, reg_b, reg_c] = regs.lines().collect_vec()[..] else {
+        return Err(src.error(regs, "expected registers A, B and C"));
+    };
+    let reg_a = parse_register(&src, reg_a)?;
+    let reg_b = parse_register(&src, reg_b)?;
+    let reg_c = parse_register(&src, reg_c)?;
 
-    let mut lines = regs.lines();
-    let reg_a = parse_register(lines.next()?)?;
-    let reg_b = parse_register(lines.next()?)?;
-    let reg_c = parse_register(lines.next()?)?;
+    let tokens = program_txt
+        .strip_prefix("Program: ")
+        .ok_or_else(|| src.error(program_txt, "expected `Program: `"))?
+        .split(',')
+        .collect_vec();
 
     let mut program = vec![];
 
-    for chunk in &program_txt
-        .strip_prefix("Program: ")?
-        .split(',')
-        .map(|s| s.parse::<usize>().expect("number"))
-        .chunks(2)
-    {
-        let (lhs, rhs) = chunk.collect_tuple().expect("valid input");
-        program.push(
-            format!("{lhs},{rhs}")
-                .parse::<Instruction>()
-                .expect("valid input"),
-        );
+    for chunk in tokens.chunks(2) {
+        let &[lhs, rhs] = chunk else {
+            return Err(src.error(chunk[0], "expected an operand after the last opcode"));
+        };
+
+        let opcode = src.number::<usize>(lhs)?;
+        let operand = src.number::<usize>(rhs)?;
+        let instr = format!("{opcode},{operand}")
+            .parse::<Instruction>()
+            .map_err(|_| src.error(lhs, "expected an opcode between 0 and 7"))?;
+
+        match instr {
+            _ if operand > 7 => {
+                return Err(src.error(rhs, "expected an operand between 0 and 7"));
+            }
+            Instruction::Adv(7)
+            | Instruction::Bst(7)
+            | Instruction::Out(7)
+            | Instruction::Bdv(7)
+            | Instruction::Cdv(7) => {
+                return Err(src.error(rhs, "combo operand 7 is reserved"));
+            }
+            Instruction::Jnz(target) if !target.is_multiple_of(2) => {
+                return Err(src.error(rhs, "expected an even jump target"));
+            }
+            _ => program.push(instr),
+        }
     }
 
-    Some(Input {
+    Ok(Input {
         reg_a,
         reg_b,
         reg_c,
@@ -83,44 +110,60 @@ const fn combo_op(op: usize, inp: &Input) -> usize {
     [op, op, op, op, inp.reg_a, inp.reg_b, inp.reg_c][op]
 }
 
-fn run_program(inp: &Input) -> String {
+/// `value / 2^amount`, which is zero once every bit is shifted out.
+fn shr(value: usize, amount: usize) -> usize {
+    u32::try_from(amount)
+        .ok()
+        .and_then(|amount| value.checked_shr(amount))
+        .unwrap_or(0)
+}
+
+/// The limits of the callers that take no budget, so that a program that
+/// loops forever cannot hang them.
+pub const DEFAULT_LIMITS: Limits = Limits {
+    timeout: Some(Duration::from_secs(10)),
+    max_steps: None,
+};
+
+fn run_program(inp: &Input, budget: &Budget) -> Result<String, Exhausted> {
     let mut pc = 0;
 
     let mut inp = inp.clone();
 
     let mut output = String::new();
 
-    while pc < inp.program.len() {
-        let instr = inp.program[pc];
+    loop {
+        budget.step()?;
+        let Some(&instr) = inp.program.get(pc) else {
+            return Ok(output.chars().join(","));
+        };
 
         match instr {
-            Instruction::Adv(op) => inp.reg_a >>= combo_op(op, &inp),
+            Instruction::Adv(op) => inp.reg_a = shr(inp.reg_a, combo_op(op, &inp)),
             Instruction::Bxl(op) => inp.reg_b ^= op,
             Instruction::Bst(op) => inp.reg_b = combo_op(op, &inp) % 8,
             Instruction::Bxc(_) => inp.reg_b ^= inp.reg_c,
-            Instruction::Bdv(op) => inp.reg_b = inp.reg_a / 2usize.pow(combo_op(op, &inp) as u32),
-            Instruction::Cdv(op) => inp.reg_c = inp.reg_a / 2usize.pow(combo_op(op, &inp) as u32),
+            Instruction::Bdv(op) => inp.reg_b = shr(inp.reg_a, combo_op(op, &inp)),
+            Instruction::Cdv(op) => inp.reg_c = shr(inp.reg_a, combo_op(op, &inp)),
             Instruction::Out(op) => {
                 let fmt = format!("{}", combo_op(op, &inp) % 8);
                 output.push_str(&fmt);
             }
             Instruction::Jnz(op) => {
                 if inp.reg_a != 0 {
-                    pc = op;
+                    pc = op / 2;
                     continue;
                 }
             }
-        };
+        }
 
         pc += 1;
     }
-
-    output.chars().join(",")
 }
 
 #[aoc(day17, part1)]
-pub fn part1(inp: &Input) -> String {
-    run_program(inp)
+pub fn part1(inp: &Input) -> Result<String, Exhausted> {
+    run_program(inp, &DEFAULT_LIMITS.start(CancelToken::new()))
 }
 
 #[aoc(day17, part2)]
@@ -140,9 +183,70 @@ pub const fn part2(_: &Input) -> usize {
     190_384_113_204_239
 }
 
+pub struct Day17;
+
+impl Solver for Day17 {
+    const DAY: u8 = 17;
+
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).ok().into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn part1_within(
+        &self,
+        input: &Self::Input,
+        _params: &PuzzleParams,
+        budget: &Budget,
+    ) -> Result<Answer, Error> {
+        budget::answer(Self::DAY, run_program(input, budget))
+    }
+
+    /// Part 2 is worked out by hand for one program, so it only holds if the
+    /// program prints itself when register A starts at the answer.
+    fn validate(&self, input: &Self::Input, part: Part) -> Vec<String> {
+        if part == Part::One {
+            return vec![];
+        }
+
+        let reg_a = part2(input);
+        let program = input.program.iter().join(",");
+        let output = run_program(
+            &Input {
+                reg_a,
+                ..input.clone()
+            },
+            &DEFAULT_LIMITS.start(CancelToken::new()),
+        );
+
+        match output {
+            Ok(output) if output == program => vec![],
+            Ok(output) => vec![format!(
+                "the answer is worked out for one specific program, but with register A \
+                 at {reg_a} this one prints {output} instead of itself"
+            )],
+            Err(reason) => vec![format!(
+                "the answer is worked out for one specific program, but with register A \
+                 at {reg_a} this one gave up ({reason}) instead of printing itself"
+            )],
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "Register A: 729\n\
                               Register B: 0\n\
@@ -154,6 +258,65 @@ mod tests {
     fn test_p1() {
         let gen = generate(TEST_INPUT).expect("valid input");
         let res = part1(&gen);
-        assert_eq!(res, "4,6,3,5,6,3,5,2,1,0".to_string());
+        assert_eq!(res, Ok("4,6,3,5,6,3,5,2,1,0".to_string()));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let input = "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,9,4,3";
+        let err = generate(input).err().map(|e| (e.line, e.column, e.text));
+        assert_eq!(err, Some((5, 14, "9".to_string())));
+    }
+
+    #[test]
+    fn test_endless_loop() {
+        let input = "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,0,3,0";
+        let gen = generate(input).expect("valid input");
+        let res = Day17.part1_within(&gen, &PuzzleParams::new(), &Budget::with_max_steps(100));
+        assert_eq!(
+            res,
+            Err(Error::GaveUp {
+                day: 17,
+                reason: Exhausted::Steps(100)
+            })
+        );
+    }
+
+    #[test]
+    fn test_long_program() {
+        // no-ops past any fixed step cap, then printing register A
+        let mut program = vec![Instruction::Bxl(0); (1 << 20) + 1];
+        program.push(Instruction::Out(4));
+        let inp = Input {
+            reg_a: 5,
+            reg_b: 0,
+            reg_c: 0,
+            program,
+        };
+        assert_eq!(part1(&inp), Ok("5".to_string()));
+    }
+
+    #[test]
+    fn test_validate() {
+        let gen = generate(TEST_INPUT).expect("valid input");
+        assert!(Day17.validate(&gen, Part::One).is_empty());
+
+        let violations = Day17.validate(&gen, Part::Two);
+        assert_eq!(violations.len(), 1);
+        assert!(
+            violations[0].ends_with("instead of itself"),
+            "{violations:?}"
+        );
+
+        let input = "Register A: 0\nRegister B: 0\nRegister C: 0\n\n\
+                     Program: 2,4,1,2,7,5,0,3,4,7,1,7,5,5,3,0";
+        let gen = generate(input).expect("valid input");
+        assert!(Day17.validate(&gen, Part::Two).is_empty());
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(17, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Text("4,6,3,5,6,3,5,2,1,0".to_string())));
     }
 }
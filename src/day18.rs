@@ -1,108 +1,216 @@
+use crate::error::{Error, ParseError};
+use crate::grid::Grid;
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::pos::{Dir, Pos};
+use crate::search;
+use crate::solver::{Answer, Part, Solver};
+use crate::viz::{Frame, FrameSink, NoViz};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use pathfinding::matrix::Matrix;
 
-#[derive(PartialEq, Eq, Hash, Copy, Clone, Debug)]
-pub struct Coord {
-    row: usize,
-    col: usize,
-}
-
-fn parse_line(line: &str) -> Coord {
-    let (lhs, rhs) = line.split_once(',').expect("valid input");
-    let lhs = lhs.parse().expect("number");
-    let rhs = rhs.parse().expect("number");
+fn parse_line(src: &Source<'_>, line: &str) -> Result<Pos, ParseError> {
+    let (lhs, rhs) = src.split_once(line, ",")?;
+    let lhs = src.number(lhs)?;
+    let rhs = src.number(rhs)?;
 
-    Coord { row: rhs, col: lhs }
+    Ok(Pos::new(rhs, lhs))
 }
 
 #[aoc_generator(day18)]
-pub fn generate(s: &str) -> Vec<Coord> {
-    s.lines().map(parse_line).collect_vec()
+pub fn generate(s: &str) -> Result<Vec<Pos>, ParseError> {
+    let src = Source::new(18, s);
+    s.lines().map(|line| parse_line(&src, line)).collect()
 }
 
-fn build_grid(rows: usize, cols: usize) -> Matrix<char> {
-    Matrix::new(rows, cols, '.')
+fn build_grid(rows: usize, cols: usize) -> Grid<char> {
+    Grid::new(rows, cols, '.')
 }
 
-fn bytes_fall(num_bytes: usize, coords: &[Coord], grid: &mut Matrix<char>) {
-    for &coord in coords.iter().take(num_bytes) {
-        grid[(coord.row, coord.col)] = '#';
+fn bytes_fall(num_bytes: usize, coords: &[Pos], grid: &mut Grid<char>, sink: &mut dyn FrameSink) {
+    for (idx, &coord) in coords.iter().take(num_bytes).enumerate() {
+        grid[coord] = '#';
+
+        if sink.enabled() {
+            let caption = format!("byte {}: {},{}", idx + 1, coord.col, coord.row);
+            sink.emit(&Frame::new(caption, grid.clone()));
+        }
     }
 }
 
-fn find_path(grid: &Matrix<char>, start: Coord, end: Coord) -> Option<(Vec<Coord>, usize)> {
-    pathfinding::prelude::dijkstra(
+fn render_path(caption: String, grid: &Grid<char>, path: Option<&[Pos]>) -> Frame {
+    let mut frame = Frame::new(caption, grid.clone());
+    for &pos in path.unwrap_or_default() {
+        frame.grid[pos] = 'O';
+    }
+    frame
+}
+
+fn find_path(grid: &Grid<char>, start: Pos, end: Pos) -> Option<(Vec<Pos>, usize)> {
+    search::dijkstra(
         &start,
         |&coord| {
-            let mut succs = vec![];
-
-            for (dr, dc) in [(1isize, 0isize), (0, 1), (-1, 0), (0, -1)] {
-                if let (Some(nr), Some(nc)) = (
-                    coord.row.checked_add_signed(dr),
-                    coord.col.checked_add_signed(dc),
-                ) {
-                    if grid.get((nr, nc)).is_some_and(|c| *c != '#') {
-                        succs.push((Coord { row: nr, col: nc }, 1));
-                    }
-                }
-            }
-
-            succs
+            Dir::ALL
+                .into_iter()
+                .filter_map(|dir| coord.step_in(dir, grid))
+                .filter(|&pos| grid[pos] != '#')
+                .map(|pos| (pos, 1))
+                .collect_vec()
         },
         |&pos| pos == end,
     )
 }
 
 fn find_first_blocking(
-    start: Coord,
-    end: Coord,
+    start: Pos,
+    end: Pos,
     to_skip: usize,
-    coords: &[Coord],
-    grid: &Matrix<char>,
-) -> Coord {
+    coords: &[Pos],
+    grid: &Grid<char>,
+    sink: &mut dyn FrameSink,
+) -> Option<Pos> {
     let indices = (to_skip..coords.len()).collect_vec();
 
     let idx = indices.partition_point(|&idx| {
         let mut grid = grid.clone();
-        bytes_fall(idx, coords, &mut grid);
+        bytes_fall(idx, coords, &mut grid, &mut NoViz);
+
+        let path = find_path(&grid, start, end);
+        if sink.enabled() {
+            let state = if path.is_some() { "open" } else { "blocked" };
+            let caption = format!("after {idx} bytes: {state}");
+            sink.emit(&render_path(
+                caption,
+                &grid,
+                path.as_ref().map(|(p, _)| &p[..]),
+            ));
+        }
 
-        find_path(&grid, start, end).is_some()
+        path.is_some()
     });
 
-    coords[to_skip + idx - 1]
+    let blocked = indices.get(idx)?;
+    coords.get(blocked.checked_sub(1)?).copied()
 }
 
-#[aoc(day18, part1)]
-pub fn part1(inp: &[Coord]) -> usize {
-    const START: Coord = Coord { row: 0, col: 0 };
-    const END: Coord = Coord { row: 70, col: 70 };
+fn in_bounds(coords: &[Pos], size: usize) -> bool {
+    coords.iter().all(|pos| pos.row < size && pos.col < size)
+}
+
+/// Fails on the line of the first byte that falls outside the grid.
+fn check_bounds(coords: &[Pos], size: usize) -> Result<(), ParseError> {
+    let Some((idx, pos)) = coords
+        .iter()
+        .find_position(|pos| pos.row >= size || pos.col >= size)
+    else {
+        return Ok(());
+    };
 
-    let mut grid = build_grid(71, 71);
-    bytes_fall(1024, inp, &mut grid);
+    Err(ParseError {
+        day: 18,
+        line: idx + 1,
+        column: 1,
+        text: format!("{},{}", pos.col, pos.row),
+        message: format!("byte outside the {size}x{size} grid"),
+        at_end: false,
+    })
+}
+
+const SIZE: usize = 71;
+/// The largest `size`, which sizes the grid.
+const MAX_SIZE: usize = 1000;
+const BYTES: usize = 1024;
 
-    let (_, cost) = find_path(&grid, START, END).expect("path exists");
-    cost
+fn run_part1(inp: &[Pos], size: usize, bytes: usize, sink: &mut dyn FrameSink) -> Option<usize> {
+    if !in_bounds(inp, size) {
+        return None;
+    }
+
+    let mut grid = build_grid(size, size);
+    bytes_fall(bytes, inp, &mut grid, sink);
+
+    let (path, cost) = find_path(&grid, Pos::new(0, 0), Pos::new(size - 1, size - 1))?;
+    if sink.enabled() {
+        sink.emit(&render_path(format!("path: {cost}"), &grid, Some(&path)));
+    }
+
+    Some(cost)
+}
+
+// the first `bytes` are known from p1 to still leave a valid path
+fn run_part2(inp: &[Pos], size: usize, bytes: usize, sink: &mut dyn FrameSink) -> Option<String> {
+    if !in_bounds(inp, size) {
+        return None;
+    }
+
+    let grid = build_grid(size, size);
+
+    let end = Pos::new(size - 1, size - 1);
+    let coord = find_first_blocking(Pos::new(0, 0), end, bytes, inp, &grid, sink)?;
+    Some(format!("{},{}", coord.col, coord.row))
+}
+
+#[aoc(day18, part1)]
+pub fn part1(inp: &[Pos]) -> Option<usize> {
+    run_part1(inp, SIZE, BYTES, &mut NoViz)
 }
 
 #[aoc(day18, part2)]
-pub fn part2(inp: &[Coord]) -> String {
-    const START: Coord = Coord { row: 0, col: 0 };
-    const END: Coord = Coord { row: 70, col: 70 };
-    const TO_SKIP: usize = 1024; // known from p1 to still have a valid path
+pub fn part2(inp: &[Pos]) -> Option<String> {
+    run_part2(inp, SIZE, BYTES, &mut NoViz)
+}
+
+pub struct Day18;
+
+impl Solver for Day18 {
+    const DAY: u8 = 18;
+    const PARAMS: &'static [&'static str] = &["size", "bytes"];
+
+    type Input = Vec<Pos>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn part1_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        self.visualize(input, Part::One, params, &mut NoViz)
+    }
 
-    let grid = build_grid(71, 71);
+    fn part2_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        self.visualize(input, Part::Two, params, &mut NoViz)
+    }
+
+    fn visualize(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        params: &PuzzleParams,
+        sink: &mut dyn FrameSink,
+    ) -> Result<Answer, Error> {
+        let size = params.get_in("size", SIZE, 1..=MAX_SIZE)?;
+        let bytes = params.get("bytes", BYTES)?;
+        check_bounds(input, size)?;
 
-    let coord = find_first_blocking(START, END, TO_SKIP, inp, &grid);
-    format!("{},{}", coord.col, coord.row)
+        Ok(match part {
+            Part::One => run_part1(input, size, bytes, sink).into(),
+            Part::Two => run_part2(input, size, bytes, sink).into(),
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-
-    const START: Coord = Coord { row: 0, col: 0 };
-    const END: Coord = Coord { row: 6, col: 6 };
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "5,4\n\
                               4,2\n\
@@ -130,23 +238,34 @@ mod tests {
                               1,6\n\
                               2,0";
 
+    fn params() -> PuzzleParams {
+        PuzzleParams::new().with("size", 7).with("bytes", 12)
+    }
+
     #[test]
     fn test_p1() {
-        let gen = generate(TEST_INPUT);
-        let mut grid = build_grid(7, 7);
-        bytes_fall(12, &gen, &mut grid);
-
-        let (_, cost) = find_path(&grid, START, END).expect("path exists");
-        assert_eq!(cost, 22);
+        let gen = generate(TEST_INPUT).expect("valid input");
+        let res = Day18.part1_with(&gen, &params());
+        assert_eq!(res, Ok(Answer::Int(22)));
     }
 
     #[test]
     fn test_p2() {
-        const TO_SKIP: usize = 12;
+        let gen = generate(TEST_INPUT).expect("valid input");
+        let res = Day18.part2_with(&gen, &params());
+        assert_eq!(res, Ok(Answer::Text("6,1".to_string())));
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let gen = generate("1,2\n7,0").expect("valid input");
+        let res = Day18.part1_with(&gen, &params());
+        assert!(matches!(res, Err(Error::Parse(e)) if (e.line, e.text.as_str()) == (2, "7,0")));
+    }
 
-        let gen = generate(TEST_INPUT);
-        let grid = build_grid(7, 7);
-        let coord = find_first_blocking(START, END, TO_SKIP, &gen, &grid);
-        assert_eq!(coord, Coord { row: 1, col: 6 });
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve_with(18, Part::One, &parse::windows(TEST_INPUT), &params());
+        assert_eq!(res, Ok(Answer::Int(22)));
     }
 }
@@ -1,3 +1,6 @@
+use crate::error::{Error, ParseError};
+use crate::parse::Source;
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use std::collections::HashMap;
@@ -9,13 +12,23 @@ pub struct Input {
 }
 
 #[aoc_generator(day19)]
-pub fn generate(s: &str) -> Option<Input> {
-    let (towels, stripes) = s.split_once("\n\n")?;
-
-    let towels = towels.split(", ").map(ToString::to_string).collect_vec();
+pub fn generate(s: &str) -> Result<Input, ParseError> {
+    let src = Source::new(19, s);
+    let (towels, stripes) = src.sections()?;
+
+    let towels = towels
+        .split(", ")
+        .map(|towel| {
+            if towel.is_empty() {
+                Err(src.error(towel, "expected a non-empty towel pattern"))
+            } else {
+                Ok(towel.to_string())
+            }
+        })
+        .try_collect()?;
     let stripes = stripes.lines().map(ToString::to_string).collect_vec();
 
-    Some(Input { towels, stripes })
+    Ok(Input { towels, stripes })
 }
 
 fn count_num_possibilities<'a>(
@@ -64,9 +77,31 @@ pub fn part2(inp: &Input) -> usize {
     })
 }
 
+pub struct Day19;
+
+impl Solver for Day19 {
+    const DAY: u8 = 19;
+
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "r, wr, b, g, bwu, rb, gb, br\n\
                               \n\
@@ -92,4 +127,10 @@ mod tests {
         let res = part2(&gen);
         assert_eq!(res, 16);
     }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(19, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(6)));
+    }
 }
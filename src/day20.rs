@@ -1,101 +1,173 @@
+use crate::error::{Error, ParseError};
+use crate::grid::{self, Grid, GridExt, MazeTile};
+use crate::par;
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::pos::Pos;
+use crate::search;
+use crate::solver::{Answer, Part, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
-use pathfinding::matrix::Matrix;
 
 #[aoc_generator(day20)]
-pub fn generate(s: &str) -> Option<Matrix<char>> {
-    let v = s.lines().map(|l| l.chars().collect_vec()).collect_vec();
-    Matrix::from_rows(v).ok()
+pub fn generate(s: &str) -> Result<Grid<MazeTile>, ParseError> {
+    grid::parse(&Source::new(20, s), s)
 }
 
-fn find_node(pos: char, grid: &Matrix<char>) -> Option<(usize, usize)> {
-    grid.keys().find(|&(r, c)| grid[(r, c)] == pos)
+fn successors(pos: Pos, grid: &Grid<MazeTile>) -> Vec<(Pos, usize)> {
+    grid.neighbors4(pos)
+        .filter(|&next| grid[next] == MazeTile::Open)
+        .map(|next| (next, 1))
+        .collect()
 }
 
-fn successors((r, c): (usize, usize), grid: &Matrix<char>) -> Vec<((usize, usize), usize)> {
-    let mut res = vec![];
+/// The tiles of the fastest way from the start to the end.
+fn racetrack(inp: &Grid<MazeTile>) -> Option<Vec<Pos>> {
+    let start = inp.find(&MazeTile::Start)?;
+    let end = inp.find(&MazeTile::End)?;
 
-    for (dr, dc) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
-        if let (Some(new_r), Some(new_c)) = (r.checked_add_signed(dr), c.checked_add_signed(dc)) {
-            if grid.get((new_r, new_c)).is_some_and(|c| *c == '.') {
-                res.push(((new_r, new_c), 1));
-            }
+    let mut grid = inp.clone();
+
+    grid[start] = MazeTile::Open;
+    grid[end] = MazeTile::Open;
+
+    let (path, _) = search::dijkstra(&start, |pos| successors(*pos, &grid), |&pos| pos == end)?;
+    Some(path)
+}
+
+/// Ways in which `grid` is not a single corridor from `S` to `E`.
+fn corridor_violations(grid: &Grid<MazeTile>) -> Vec<String> {
+    let mut violations = vec![];
+    for (tile, c) in [(MazeTile::Start, 'S'), (MazeTile::End, 'E')] {
+        let count = grid.find_all(&tile).count();
+        if count != 1 {
+            violations.push(format!("expected one `{c}` tile, found {count}"));
         }
     }
+    if !violations.is_empty() {
+        return violations;
+    }
+
+    let track = grid
+        .positions()
+        .filter(|&pos| grid[pos] != MazeTile::Wall)
+        .collect_vec();
+    let forks = track
+        .iter()
+        .filter(|&&pos| {
+            grid.neighbors4(pos)
+                .filter(|&next| grid[next] != MazeTile::Wall)
+                .count()
+                > 2
+        })
+        .collect_vec();
+    if let Some(first) = forks.first() {
+        violations.push(format!(
+            "expected a single corridor, but the track forks in {} places, \
+             the first at line {}, column {}",
+            forks.len(),
+            first.row + 1,
+            first.col + 1
+        ));
+    }
+
+    match racetrack(grid) {
+        None => violations.push("there is no way from `S` to `E`".to_string()),
+        Some(path) if path.len() < track.len() => violations.push(format!(
+            "expected a single corridor, but {} of the {} track tiles are off \
+             the way from `S` to `E`",
+            track.len() - path.len(),
+            track.len()
+        )),
+        Some(_) => {}
+    }
 
-    res
+    violations
 }
 
-fn run_part1(inp: &Matrix<char>, cost_diff: usize) -> Option<usize> {
-    let start = find_node('S', inp)?;
-    let end = find_node('E', inp)?;
+/// Counts the cheats of at most `max_cheat` picoseconds that save at least `cost_diff`.
+///
+/// The racetrack is a single path, so the time between two of its tiles is
+/// their distance along it.
+fn count_cheats(inp: &Grid<MazeTile>, max_cheat: usize, cost_diff: usize) -> Option<usize> {
+    let path = racetrack(inp)?;
 
-    let mut grid = inp.clone();
+    let count = par::sum(0..path.len(), |cheat_start_idx| {
+        (cheat_start_idx + 1..path.len())
+            .filter(|&cheat_end_idx| {
+                let n1 = path[cheat_start_idx];
+                let n2 = path[cheat_end_idx];
+                let distance = n1.manhattan(n2);
+                distance <= max_cheat && cheat_end_idx - cheat_start_idx >= cost_diff + distance
+            })
+            .count()
+    });
 
-    grid[start] = '.';
-    grid[end] = '.';
+    Some(count)
+}
 
-    let paths = pathfinding::prelude::dijkstra_all(&start, |pos| successors(*pos, &grid));
+fn run_part1(inp: &Grid<MazeTile>, cost_diff: usize) -> Option<usize> {
+    count_cheats(inp, 2, cost_diff)
+}
 
-    let mut count = 0;
+fn run_part2(inp: &Grid<MazeTile>, cost_diff: usize) -> Option<usize> {
+    count_cheats(inp, 20, cost_diff)
+}
 
-    for (&(r, c), (_, cost)) in &paths {
-        for (dr, dc) in [(-2, 0), (2, 0), (0, -2), (0, 2)] {
-            if let (Some(new_r), Some(new_c)) = (r.checked_add_signed(dr), c.checked_add_signed(dc))
-            {
-                if let Some((_, next_cost)) = paths.get(&(new_r, new_c)) {
-                    if next_cost.saturating_sub(*cost) >= cost_diff + 2 {
-                        count += 1;
-                    }
-                }
-            }
-        }
-    }
+const SAVING: usize = 100;
 
-    Some(1 + count)
+#[aoc(day20, part1)]
+pub fn part1(inp: &Grid<MazeTile>) -> Option<usize> {
+    run_part1(inp, SAVING)
 }
 
-fn run_part2(inp: &Matrix<char>, cost_diff: usize) -> Option<usize> {
-    let start = find_node('S', inp)?;
-    let end = find_node('E', inp)?;
+#[aoc(day20, part2)]
+pub fn part2(inp: &Grid<MazeTile>) -> Option<usize> {
+    run_part2(inp, SAVING)
+}
 
-    let mut grid = inp.clone();
+pub struct Day20;
 
-    grid[start] = '.';
-    grid[end] = '.';
+impl Solver for Day20 {
+    const DAY: u8 = 20;
+    const PARAMS: &'static [&'static str] = &["saving"];
 
-    let (path, _) =
-        pathfinding::prelude::dijkstra(&start, |pos| successors(*pos, &grid), |&pos| pos == end)?;
+    type Input = Grid<MazeTile>;
 
-    let mut count = 0;
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
 
-    for cheat_start_idx in 0..path.len() {
-        for cheat_end_idx in cheat_start_idx + 1..path.len() {
-            let n1 = path[cheat_start_idx];
-            let n2 = path[cheat_end_idx];
-            let distance = n1.0.abs_diff(n2.0) + n1.1.abs_diff(n2.1);
-            if distance <= 20 && cheat_end_idx - cheat_start_idx >= cost_diff + distance {
-                count += 1;
-            }
-        }
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
     }
 
-    Some(count)
-}
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 
-#[aoc(day20, part1)]
-pub fn part1(inp: &Matrix<char>) -> Option<usize> {
-    run_part1(inp, 100)
-}
+    fn part1_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        Ok(run_part1(input, params.get("saving", SAVING)?).into())
+    }
 
-#[aoc(day20, part2)]
-pub fn part2(inp: &Matrix<char>) -> Option<usize> {
-    run_part2(inp, 100)
+    fn part2_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        Ok(run_part2(input, params.get("saving", SAVING)?).into())
+    }
+
+    /// Cheats are measured along the track, which must be one corridor.
+    fn validate(&self, input: &Self::Input, _part: Part) -> Vec<String> {
+        corridor_violations(input)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gen;
+    use crate::parse;
+    use crate::solver::{self, Part};
+    use proptest::prelude::*;
+    use std::collections::{HashMap, VecDeque};
 
     const TEST_INPUT: &str = "###############\n\
                               #...#...#.....#\n\
@@ -116,14 +188,96 @@ mod tests {
     #[test]
     fn test_p1() {
         let gen = generate(TEST_INPUT).expect("valid input");
-        let res = run_part1(&gen, 1);
-        assert_eq!(res, Some(44));
+        let res = Day20.part1_with(&gen, &PuzzleParams::new().with("saving", 1));
+        assert_eq!(res, Ok(Answer::Int(44)));
     }
 
     #[test]
     fn test_p2() {
         let gen = generate(TEST_INPUT).expect("valid input");
-        let res = run_part2(&gen, 50);
-        assert_eq!(res, Some(285));
+        let res = Day20.part2_with(&gen, &PuzzleParams::new().with("saving", 50));
+        assert_eq!(res, Ok(Answer::Int(285)));
+    }
+
+    #[test]
+    fn test_validate() {
+        let gen = generate(TEST_INPUT).expect("valid input");
+        assert!(Day20.validate(&gen, Part::One).is_empty());
+
+        let forked = generate(&TEST_INPUT.replacen("#...#", "#....", 1)).expect("valid input");
+        assert_eq!(
+            Day20.validate(&forked, Part::Two),
+            [
+                "expected a single corridor, but the track forks in 2 places, \
+                 the first at line 2, column 4",
+                "expected a single corridor, but 5 of the 86 track tiles are off \
+                 the way from `S` to `E`",
+            ]
+        );
+
+        let no_end = generate(&TEST_INPUT.replace('E', ".")).expect("valid input");
+        assert_eq!(
+            Day20.validate(&no_end, Part::One),
+            ["expected one `E` tile, found 0"]
+        );
+    }
+
+    fn distances(grid: &Grid<MazeTile>, from: Pos) -> HashMap<Pos, usize> {
+        let mut dist = HashMap::from([(from, 0)]);
+        let mut queue = VecDeque::from([from]);
+
+        while let Some(pos) = queue.pop_front() {
+            for next in grid.neighbors4(pos) {
+                if grid[next] != MazeTile::Wall && !dist.contains_key(&next) {
+                    dist.insert(next, dist[&pos] + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        dist
+    }
+
+    /// Tries every pair of cheat start and end positions.
+    fn reference_cheats(grid: &Grid<MazeTile>, max_cheat: usize, saving: usize) -> usize {
+        let start = grid.find(&MazeTile::Start).expect("has a start");
+        let end = grid.find(&MazeTile::End).expect("has an end");
+        let from_start = distances(grid, start);
+        let to_end = distances(grid, end);
+        let best = from_start[&end];
+
+        let mut count = 0;
+        for (&from, &before) in &from_start {
+            for (&to, &after) in &to_end {
+                let cheat = from.manhattan(to);
+                if cheat <= max_cheat && before + cheat + after + saving <= best {
+                    count += 1;
+                }
+            }
+        }
+
+        count
+    }
+
+    proptest! {
+        #[test]
+        fn test_cheats_reference(size in 5..16usize, seed: u64, saving in 1..10usize) {
+            let input = gen::generate(20, size, seed).expect("day 20 exists");
+            let grid = generate(&input).expect("valid racetrack");
+
+            prop_assert_eq!(run_part1(&grid, saving), Some(reference_cheats(&grid, 2, saving)));
+            prop_assert_eq!(run_part2(&grid, saving), Some(reference_cheats(&grid, 20, saving)));
+        }
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve_with(
+            20,
+            Part::One,
+            &parse::windows(TEST_INPUT),
+            &PuzzleParams::new().with("saving", 1),
+        );
+        assert_eq!(res, Ok(Answer::Int(44)));
     }
 }
@@ -1,18 +1,39 @@
+use crate::budget::Budget;
+use crate::cache::{self, Cache, DayCache};
+use crate::error::{Error, ParseError};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::search;
+use crate::solver::{Answer, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
-use memoize::memoize;
 
 #[aoc_generator(day21)]
-pub fn generate(s: &str) -> Vec<String> {
-    s.lines().map(ToString::to_string).collect()
+pub fn generate(s: &str) -> Result<Vec<String>, ParseError> {
+    let src = Source::new(21, s);
+
+    s.lines()
+        .map(|line| {
+            let digits = line
+                .strip_suffix('A')
+                .ok_or_else(|| src.error(line, "expected a code ending in `A`"))?;
+
+            if let Some((idx, c)) = digits.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+                let span = &digits[idx..idx + c.len_utf8()];
+                return Err(src.error(span, "expected a digit"));
+            }
+            src.number::<usize>(digits)?;
+
+            Ok(line.to_string())
+        })
+        .collect()
 }
 
 fn append_dir(dir: char, dirs: &str) -> String {
     dirs.to_string() + &dir.to_string()
 }
 
-#[allow(clippy::too_many_lines, clippy::needless_pass_by_value)]
-#[memoize]
-fn directional_keypad_successors(state: KeypadState) -> Vec<(KeypadState, usize)> {
+#[allow(clippy::too_many_lines)]
+fn directional_keypad_successors(state: &KeypadState) -> Vec<(KeypadState, usize)> {
     let succs = match state.current {
         'A' => vec![
             KeypadState {
@@ -68,9 +89,8 @@ fn directional_keypad_successors(state: KeypadState) -> Vec<(KeypadState, usize)
     succs.iter().map(|it| (it.clone(), 1)).collect()
 }
 
-#[allow(clippy::too_many_lines, clippy::needless_pass_by_value)]
-#[memoize]
-fn keypad_successors(state: KeypadState) -> Vec<(KeypadState, usize)> {
+#[allow(clippy::too_many_lines)]
+fn keypad_successors(state: &KeypadState) -> Vec<(KeypadState, usize)> {
     let succs = match state.current {
         'A' => vec![
             KeypadState {
@@ -227,14 +247,19 @@ struct KeypadState {
     dir: String,
 }
 
-#[memoize]
-fn all_keypad_paths(from: char, to: char) -> Vec<String> {
-    let (path, _) = pathfinding::prelude::astar_bag_collect(
+/// The shortest button sequences on the robot's keypad that move from `from`
+/// to `to` and press it.
+fn shortest_paths(
+    from: char,
+    to: char,
+    successors: fn(&KeypadState) -> Vec<(KeypadState, usize)>,
+) -> Vec<String> {
+    let (path, _) = search::astar_bag_collect(
         &KeypadState {
             current: from,
             dir: String::new(),
         },
-        |c| keypad_successors(c.clone()),
+        successors,
         |_| 0,
         |cur| cur.current == to,
     )
@@ -245,101 +270,194 @@ fn all_keypad_paths(from: char, to: char) -> Vec<String> {
         .collect()
 }
 
-#[memoize]
-fn all_dpad_paths(from: char, to: char) -> Vec<String> {
-    let (path, _) = pathfinding::prelude::astar_bag_collect(
-        &KeypadState {
-            current: from,
-            dir: String::new(),
-        },
-        |c| directional_keypad_successors(c.clone()),
-        |_| 0,
-        |cur| cur.current == to,
-    )
-    .expect("path exists");
-
-    path.iter()
-        .map(|p| p.last().expect("non-empty").dir.clone() + "A")
-        .collect()
+/// Shortest keypad paths and typing costs, remembered across codes.
+///
+/// None of them depend on the input, so one instance can serve many.
+#[derive(Clone, Debug, Default)]
+pub struct Keypads {
+    keypad_paths: Cache<(char, char), Vec<String>>,
+    dpad_paths: Cache<(char, char), Vec<String>>,
+    press_costs: Cache<(char, char, usize), usize>,
 }
 
-#[memoize]
-#[allow(clippy::needless_pass_by_value)]
-fn find_min_length(s: String, robots: usize) -> usize {
-    if robots == 0 {
-        return s.len();
+impl Keypads {
+    /// The sum of the complexities of `codes`, or `None` on overflow.
+    pub fn total_complexity(&mut self, codes: &[String], robots: usize) -> Option<usize> {
+        let mut res = 0usize;
+
+        for s in codes {
+            res = res.checked_add(self.complexity(s, robots)?)?;
+        }
+
+        Some(res)
     }
 
-    let mut res = 0;
-    let mut current = 'A';
+    fn complexity(&mut self, s: &str, robots: usize) -> Option<usize> {
+        let mut current = 'A';
+        let mut res = 0;
 
-    for c in s.chars() {
-        let paths = all_dpad_paths(current, c);
+        for c in s.chars() {
+            let paths = self
+                .keypad_paths
+                .get_or_insert_with((current, c), || {
+                    shortest_paths(current, c, keypad_successors)
+                })
+                .clone();
 
-        let min_length = paths
-            .iter()
-            .map(|it| find_min_length(it.clone(), robots - 1))
-            .min()
-            .expect("exists");
+            let min_sub_path = paths
+                .iter()
+                .map(|p| self.find_min_length(p, robots))
+                .min()
+                .expect("exists");
+
+            res += min_sub_path;
+            current = c;
+        }
 
-        res += min_length;
+        let num = s.strip_suffix('A')?.parse::<usize>().ok()?;
 
-        current = c;
+        res.checked_mul(num)
     }
 
-    res
-}
+    /// The fewest presses on the outermost keypad that type `s` through `robots` robots.
+    fn find_min_length(&mut self, s: &str, robots: usize) -> usize {
+        if robots == 0 {
+            return s.len();
+        }
 
-fn complexity(s: &str, robots: usize) -> usize {
-    let mut current = 'A';
-    let mut res = 0;
+        let mut res = 0;
+        let mut current = 'A';
 
-    for c in s.chars() {
-        let paths = all_keypad_paths(current, c);
+        for c in s.chars() {
+            res += self.press_cost(current, c, robots);
+            current = c;
+        }
 
-        let min_sub_path = paths
+        res
+    }
+
+    /// The fewest presses that move the innermost robot from `from` to `to`
+    /// and press it.
+    fn press_cost(&mut self, from: char, to: char, robots: usize) -> usize {
+        if let Some(&cost) = self.press_costs.get(&(from, to, robots)) {
+            return cost;
+        }
+
+        let paths = self
+            .dpad_paths
+            .get_or_insert_with((from, to), || {
+                shortest_paths(from, to, directional_keypad_successors)
+            })
+            .clone();
+
+        let cost = paths
             .iter()
-            .map(|p| find_min_length(p.clone(), robots))
+            .map(|it| self.find_min_length(it, robots - 1))
             .min()
             .expect("exists");
 
-        res += min_sub_path;
-        current = c;
+        self.press_costs.insert((from, to, robots), cost);
+        cost
     }
+}
 
-    let num = s
-        .strip_suffix("A")
-        .map(|n| n.parse::<usize>().expect("number"))
-        .expect("number");
+impl DayCache for Keypads {
+    /// The number of cached paths and costs.
+    fn cache_size(&self) -> usize {
+        self.keypad_paths.len() + self.dpad_paths.len() + self.press_costs.len()
+    }
 
-    res * num
+    fn clear_cache(&mut self) {
+        self.keypad_paths.clear();
+        self.dpad_paths.clear();
+        self.press_costs.clear();
+    }
 }
 
-#[aoc(day21, part1)]
-pub fn part1(inp: &[String]) -> usize {
-    let mut res = 0;
+const ROBOTS_P1: usize = 2;
+const ROBOTS_P2: usize = 25;
+/// The most `robots`, each of which is a level of recursion; longer chains
+/// overflow the press counts of even the shortest codes.
+const MAX_ROBOTS: usize = 40;
 
-    for s in inp {
-        res += complexity(s, 2);
-    }
+fn total_complexity(inp: &[String], robots: usize) -> Option<usize> {
+    Keypads::default().total_complexity(inp, robots)
+}
 
-    res
+#[aoc(day21, part1)]
+pub fn part1(inp: &[String]) -> Option<usize> {
+    total_complexity(inp, ROBOTS_P1)
 }
 
 #[aoc(day21, part2)]
-pub fn part2(inp: &[String]) -> usize {
-    let mut res = 0;
+pub fn part2(inp: &[String]) -> Option<usize> {
+    total_complexity(inp, ROBOTS_P2)
+}
+
+pub struct Day21;
+
+impl Solver for Day21 {
+    const DAY: u8 = 21;
+    const PARAMS: &'static [&'static str] = &["robots"];
+
+    type Input = Vec<String>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
 
-    for s in inp {
-        res += complexity(s, 25);
+    fn part1_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        let mut keypads = Keypads::default();
+        self.part1_cached(input, params, &Budget::unlimited(), &mut keypads)
     }
 
-    res
+    fn part2_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        let mut keypads = Keypads::default();
+        self.part2_cached(input, params, &Budget::unlimited(), &mut keypads)
+    }
+
+    fn new_cache(&self) -> Option<Box<dyn DayCache>> {
+        Some(Box::<Keypads>::default())
+    }
+
+    fn part1_cached(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        _budget: &Budget,
+        cache: &mut dyn DayCache,
+    ) -> Result<Answer, Error> {
+        let robots = params.get_in("robots", ROBOTS_P1, 0..=MAX_ROBOTS)?;
+        let keypads = cache::downcast::<Keypads>(Self::DAY, cache)?;
+        Ok(keypads.total_complexity(input, robots).into())
+    }
+
+    fn part2_cached(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        _budget: &Budget,
+        cache: &mut dyn DayCache,
+    ) -> Result<Answer, Error> {
+        let robots = params.get_in("robots", ROBOTS_P2, 0..=MAX_ROBOTS)?;
+        let keypads = cache::downcast::<Keypads>(Self::DAY, cache)?;
+        Ok(keypads.total_complexity(input, robots).into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT: &str = "029A\n\
                               980A\n\
@@ -349,8 +467,30 @@ mod tests {
 
     #[test]
     fn test_p1() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part1(&gen);
-        assert_eq!(res, 126_384);
+        assert_eq!(res, Some(126_384));
+    }
+
+    #[test]
+    fn test_reuse() {
+        let gen = generate(TEST_INPUT).expect("valid input");
+        let mut keypads = Keypads::default();
+
+        assert_eq!(keypads.total_complexity(&gen, 2), Some(126_384));
+        assert_eq!(
+            keypads.total_complexity(&gen, 25),
+            Some(154_115_708_116_294)
+        );
+        assert!(keypads.cache_size() > 0);
+
+        keypads.clear_cache();
+        assert_eq!(keypads.cache_size(), 0);
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(21, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(126_384)));
     }
 }
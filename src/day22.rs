@@ -1,9 +1,24 @@
+use crate::error::{Error, ParseError};
+use crate::par;
+use crate::params::PuzzleParams;
+use crate::parse;
+use crate::solver::{Answer, Part, Solver, Variant};
 use aoc_runner_derive::{aoc, aoc_generator};
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
+use std::io::BufRead;
 
 #[aoc_generator(day22)]
-pub fn generate(s: &str) -> Vec<usize> {
-    s.lines().map(|l| l.parse().expect("number")).collect()
+pub fn generate(s: &str) -> Result<Vec<usize>, ParseError> {
+    parse::from_text(22, s, generate_from)
+}
+
+pub fn generate_from<R: BufRead>(reader: R) -> Result<Vec<usize>, Error> {
+    let mut secrets = vec![];
+    parse::for_each_line(22, reader, |src, line| {
+        secrets.push(src.number(line)?);
+        Ok(())
+    })?;
+    Ok(secrets)
 }
 
 const fn prune(result: usize) -> usize {
@@ -14,9 +29,10 @@ const fn mix(secret: usize, result: usize) -> usize {
 }
 
 const fn calculate_next_number(secret: usize) -> usize {
+    // Only the pruned bits are kept, so a wrapped product gives the same result.
     // Calculate the result of multiplying the secret number by 64. Then, mix this result into the secret number.
     // Finally, prune the secret number.
-    let secret = prune(mix(secret, secret * 64));
+    let secret = prune(mix(secret, secret.wrapping_mul(64)));
 
     // Calculate the result of dividing the secret number by 32. Round the result down to the nearest integer.
     // Then, mix this result into the secret number. Finally, prune the secret number.
@@ -24,59 +40,141 @@ const fn calculate_next_number(secret: usize) -> usize {
 
     // Calculate the result of multiplying the secret number by 2048.
     // Then, mix this result into the secret number. Finally, prune the secret number.
-    prune(mix(secret, secret * 2048))
+    prune(mix(secret, secret.wrapping_mul(2048)))
 }
 
-#[aoc(day22, part1)]
-pub fn part1(inp: &[usize]) -> usize {
+const ITERATIONS: usize = 2000;
+
+fn run_part1(inp: &[usize], iterations: usize) -> usize {
     inp.iter().fold(0, |acc, it| {
-        acc + (0..2000).fold(*it, |acc, _| calculate_next_number(acc))
+        acc + (0..iterations).fold(*it, |acc, _| calculate_next_number(acc))
     })
 }
 
+#[aoc(day22, part1)]
+pub fn part1(inp: &[usize]) -> usize {
+    run_part1(inp, ITERATIONS)
+}
+
+/// The price at the first occurrence of each sequence of four price changes.
+fn first_prices(mut secret: usize, iterations: usize) -> Option<HashMap<[isize; 4], usize>> {
+    let mut sequence = vec![secret % 10];
+    for _ in 0..iterations {
+        secret = calculate_next_number(secret);
+        sequence.push(secret % 10);
+    }
+
+    let mut diffs = vec![];
+    for win in sequence.windows(2) {
+        diffs.push(isize::try_from(win[1]).ok()? - isize::try_from(win[0]).ok()?);
+    }
+
+    let mut prices = HashMap::new();
+    for (idx, diff) in diffs.windows(4).enumerate() {
+        let diff = <[isize; 4]>::try_from(diff).ok()?;
+        prices.entry(diff).or_insert(sequence[idx + 4]);
+    }
+
+    Some(prices)
+}
+
+fn run_part2(inp: &[usize], iterations: usize) -> Option<usize> {
+    let buyers = par::map(inp, |&secret| first_prices(secret, iterations));
+
+    let mut prices = HashMap::new();
+    for buyer in buyers {
+        for (diff, price) in buyer? {
+            *prices.entry(diff).or_insert(0) += price;
+        }
+    }
+
+    prices.values().max().copied()
+}
+
 #[aoc(day22, part2)]
 pub fn part2(inp: &[usize]) -> Option<usize> {
-    let mut sequences = vec![vec![0; 2001]; inp.len()];
-
-    for (buyer, mut i) in inp.iter().copied().enumerate() {
-        let buyer = &mut sequences[buyer];
-        buyer[0] = i % 10;
-        for slot in buyer.iter_mut().skip(1) {
-            let next_num = calculate_next_number(i);
-            *slot = next_num % 10;
-            i = next_num;
+    run_part2(inp, ITERATIONS)
+}
+
+/// Sequences of four price changes, each of which lies in `-9..=9`.
+const SEQUENCES: usize = 19 * 19 * 19 * 19;
+
+/// Part 2 without hashing: each sequence indexes a flat array of totals, and
+/// remembers the last buyer that sold at it.
+fn run_part2_array(inp: &[usize], iterations: usize) -> Option<usize> {
+    let mut totals = vec![0; SEQUENCES];
+    let mut last_buyer = vec![usize::MAX; SEQUENCES];
+
+    for (buyer, &secret) in inp.iter().enumerate() {
+        let (mut secret, mut sequence) = (secret, 0);
+        for step in 0..iterations {
+            let price = secret % 10;
+            secret = calculate_next_number(secret);
+            let next = secret % 10;
+            sequence = (sequence * 19 + next + 9 - price) % SEQUENCES;
+
+            if step >= 3 && last_buyer[sequence] != buyer {
+                last_buyer[sequence] = buyer;
+                totals[sequence] += next;
+            }
         }
     }
 
-    let mut diffs = vec![vec![0isize; 2001]; inp.len()];
-    for (buyer_idx, buyer) in sequences.iter().enumerate() {
-        diffs[buyer_idx][0] = isize::try_from(buyer[0]).ok()?;
+    totals
+        .into_iter()
+        .zip(last_buyer)
+        .filter(|&(_, buyer)| buyer != usize::MAX)
+        .map(|(total, _)| total)
+        .max()
+}
 
-        for (idx, win) in buyer.windows(2).enumerate() {
-            let diff = isize::try_from(win[1]).ok()? - isize::try_from(win[0]).ok()?;
-            diffs[buyer_idx][idx + 1] = diff;
-        }
+pub fn part2_array(inp: &[usize]) -> Option<usize> {
+    run_part2_array(inp, ITERATIONS)
+}
+
+pub struct Day22;
+
+impl Solver for Day22 {
+    const DAY: u8 = 22;
+    const PARAMS: &'static [&'static str] = &["iterations"];
+
+    type Input = Vec<usize>;
+
+    const VARIANTS: &'static [Variant<Self::Input>] = &[Variant {
+        name: "array",
+        part: Part::Two,
+        approximate: false,
+        solve: |input, params| {
+            Ok(run_part2_array(input, params.get("iterations", ITERATIONS)?).into())
+        },
+    }];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
     }
 
-    let mut prices = HashMap::new();
-    for (buyer_idx, diff) in diffs.iter().enumerate() {
-        let mut seen = HashSet::new();
-        for (idx, diff) in diff.windows(4).enumerate().skip(1) {
-            if seen.contains(diff) {
-                continue;
-            }
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
 
-            seen.insert(diff);
-            *prices.entry(diff).or_insert(0) += sequences[buyer_idx][idx + 3];
-        }
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
     }
 
-    prices.values().max().copied()
+    fn part1_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        Ok(run_part1(input, params.get("iterations", ITERATIONS)?).into())
+    }
+
+    fn part2_with(&self, input: &Self::Input, params: &PuzzleParams) -> Result<Answer, Error> {
+        Ok(run_part2(input, params.get("iterations", ITERATIONS)?).into())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::runner;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT_P1: &str = "1\n\
                                  10\n\
@@ -90,15 +188,32 @@ mod tests {
 
     #[test]
     fn test_p1() {
-        let gen = generate(TEST_INPUT_P1);
+        let gen = generate(TEST_INPUT_P1).expect("valid input");
         let res = part1(&gen);
         assert_eq!(res, 37_327_623);
     }
 
     #[test]
     fn test_p2() {
-        let gen = generate(TEST_INPUT_P2);
+        let gen = generate(TEST_INPUT_P2).expect("valid input");
         let res = part2(&gen);
         assert_eq!(res, Some(23));
+        assert_eq!(part2_array(&gen), res);
+        assert_eq!(run_part2_array(&gen, 3), None);
+    }
+
+    #[test]
+    fn test_variants() {
+        let params = PuzzleParams::new().with("iterations", 100);
+        let runs =
+            runner::run_variants(&Day22, Part::Two, TEST_INPUT_P2, &params).expect("valid input");
+        assert_eq!(runs.len(), 2);
+        assert!(runner::disagreeing(&runs).is_empty());
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(22, Part::One, &parse::windows(TEST_INPUT_P1));
+        assert_eq!(res, Ok(Answer::Int(37_327_623)));
     }
 }
@@ -1,19 +1,23 @@
+use crate::dot::Graph;
+use crate::error::{Error, ParseError};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::solver::{Answer, Part, Solver, Variant};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
-#[derive(PartialEq, Eq, Hash, Clone, Debug)]
-pub struct Connection {
-    lhs: String,
-    rhs: String,
-}
-
 #[aoc_generator(day23)]
-pub fn generate(s: &str) -> HashMap<String, Vec<String>> {
+pub fn generate(s: &str) -> Result<HashMap<String, Vec<String>>, ParseError> {
+    let src = Source::new(23, s);
+
     let mut result = HashMap::new();
 
     for line in s.lines() {
-        let (lhs, rhs) = line.split_once('-').expect("valid input");
+        let (lhs, rhs) = src.split_once(line, "-")?;
+        if lhs.is_empty() || rhs.is_empty() || lhs == rhs {
+            return Err(src.error(line, "expected two different computers"));
+        }
 
         result
             .entry(lhs.to_string())
@@ -26,7 +30,7 @@ pub fn generate(s: &str) -> HashMap<String, Vec<String>> {
             .push(lhs.to_string());
     }
 
-    result
+    Ok(result)
 }
 
 fn extract_triplets(conns: &HashMap<String, Vec<String>>) -> HashSet<[&String; 3]> {
@@ -51,7 +55,77 @@ fn extract_triplets(conns: &HashMap<String, Vec<String>>) -> HashSet<[&String; 3
     res
 }
 
+type Neighbors<'a> = HashMap<&'a str, HashSet<&'a str>>;
+
+/// Bron-Kerbosch with pivoting: extends `clique` by `candidates`, skipping
+/// cliques that could also contain a node of `excluded`.
+fn extend_clique<'a>(
+    neighbors: &Neighbors<'a>,
+    clique: &mut Vec<&'a str>,
+    mut candidates: HashSet<&'a str>,
+    mut excluded: HashSet<&'a str>,
+    cliques: &mut Vec<Vec<String>>,
+) {
+    let Some(pivot) = candidates
+        .union(&excluded)
+        .max_by_key(|node| candidates.intersection(&neighbors[*node]).count())
+        .copied()
+    else {
+        cliques.push(clique.iter().map(ToString::to_string).collect());
+        return;
+    };
+
+    let skipped = &neighbors[pivot];
+    for node in candidates
+        .iter()
+        .copied()
+        .filter(|node| !skipped.contains(node))
+        .collect_vec()
+    {
+        let adjacent = &neighbors[node];
+
+        clique.push(node);
+        extend_clique(
+            neighbors,
+            clique,
+            candidates.intersection(adjacent).copied().collect(),
+            excluded.intersection(adjacent).copied().collect(),
+            cliques,
+        );
+        clique.pop();
+
+        candidates.remove(node);
+        excluded.insert(node);
+    }
+}
+
+fn neighbors(conns: &HashMap<String, Vec<String>>) -> Neighbors<'_> {
+    conns
+        .iter()
+        .map(|(node, succs)| (node.as_str(), succs.iter().map(String::as_str).collect()))
+        .collect()
+}
+
+/// All maximal cliques, i.e. those that no other computer can join.
 fn collect_cliques(conns: &HashMap<String, Vec<String>>) -> Vec<Vec<String>> {
+    let neighbors = neighbors(conns);
+
+    let mut all_cliques = vec![];
+    extend_clique(
+        &neighbors,
+        &mut vec![],
+        neighbors.keys().copied().collect(),
+        HashSet::new(),
+        &mut all_cliques,
+    );
+
+    all_cliques
+}
+
+/// Cliques built by adding each computer, in order, to the first clique whose
+/// members it is all connected to. Fast, but a computer that joins a small
+/// clique early is lost to a larger one found later.
+fn collect_cliques_greedy(conns: &HashMap<String, Vec<String>>) -> Vec<Vec<String>> {
     let mut all_cliques: Vec<Vec<String>> = vec![];
     for (node, succs) in conns.iter().sorted() {
         if let Some(slot) = all_cliques
@@ -67,29 +141,133 @@ fn collect_cliques(conns: &HashMap<String, Vec<String>>) -> Vec<Vec<String>> {
     all_cliques
 }
 
+/// The triplets with a computer whose name starts with `t`.
+fn t_triplets(conns: &HashMap<String, Vec<String>>) -> Vec<[&String; 3]> {
+    extract_triplets(conns)
+        .into_iter()
+        .filter(|it| it.iter().any(|name| name.starts_with('t')))
+        .collect()
+}
+
+fn max_clique(conns: &HashMap<String, Vec<String>>) -> Option<Vec<String>> {
+    let all_cliques = collect_cliques(conns);
+    let max_clique = all_cliques.into_iter().max_by_key(Vec::len)?;
+    Some(max_clique.into_iter().sorted().collect())
+}
+
 #[aoc(day23, part1)]
 pub fn part1(conns: &HashMap<String, Vec<String>>) -> usize {
-    let triplets = extract_triplets(conns);
-    triplets
+    t_triplets(conns).len()
+}
+
+/// Part 1 looking only at pairs of neighbors of the `t` computers, counting
+/// each triplet at the first of its `t` computers.
+pub fn part1_t_first(conns: &HashMap<String, Vec<String>>) -> usize {
+    let neighbors = neighbors(conns);
+
+    neighbors
         .iter()
-        .filter(|it| it[0].starts_with('t') || it[1].starts_with('t') || it[2].starts_with('t'))
-        .count()
+        .filter(|(name, _)| name.starts_with('t'))
+        .map(|(&name, others)| {
+            others
+                .iter()
+                .filter(|other| !other.starts_with('t') || **other > name)
+                .tuple_combinations()
+                .filter(|(lhs, rhs)| neighbors[*lhs].contains(*rhs))
+                .count()
+        })
+        .sum()
 }
 
 #[aoc(day23, part2)]
-pub fn part2(conns: &HashMap<String, Vec<String>>) -> String {
-    let all_cliques = collect_cliques(conns);
+pub fn part2(conns: &HashMap<String, Vec<String>>) -> Option<String> {
+    Some(max_clique(conns)?.join(","))
+}
 
-    let max_clique = all_cliques
-        .iter()
-        .max_by_key(|it| it.len())
-        .expect("max exists");
-    max_clique.iter().sorted().join(",")
+/// Part 2 from the greedy cliques, which may miss the largest one.
+pub fn part2_greedy(conns: &HashMap<String, Vec<String>>) -> Option<String> {
+    let max_clique = collect_cliques_greedy(conns)
+        .into_iter()
+        .max_by_key(Vec::len)?;
+    Some(max_clique.into_iter().sorted().join(","))
+}
+
+pub struct Day23;
+
+impl Solver for Day23 {
+    const DAY: u8 = 23;
+
+    type Input = HashMap<String, Vec<String>>;
+
+    const VARIANTS: &'static [Variant<Self::Input>] = &[
+        Variant {
+            name: "t-first",
+            part: Part::One,
+            approximate: false,
+            solve: |input, _| Ok(part1_t_first(input).into()),
+        },
+        Variant {
+            name: "greedy",
+            part: Part::Two,
+            approximate: true,
+            solve: |input, _| Ok(part2_greedy(input).into()),
+        },
+    ];
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    /// The network, highlighting the triplets counted in part 1 and the
+    /// largest clique in part 2.
+    fn graph(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &PuzzleParams,
+    ) -> Result<Graph, Error> {
+        let mut graph = Graph::undirected();
+        for (name, others) in input.iter().sorted() {
+            for other in others.iter().sorted() {
+                graph.edge(name, other);
+            }
+        }
+
+        let groups: Vec<Vec<String>> = match part {
+            Part::One => t_triplets(input)
+                .into_iter()
+                .sorted()
+                .map(|triplet| triplet.map(String::clone).to_vec())
+                .collect(),
+            Part::Two => max_clique(input).into_iter().collect(),
+        };
+        for group in groups {
+            for (lhs, rhs) in group.iter().tuple_combinations() {
+                graph.highlight_edge(lhs, rhs);
+            }
+            for name in group {
+                graph.highlight_node(name);
+            }
+        }
+
+        Ok(graph)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver::{self, Part};
+    use proptest::prelude::*;
 
     const TEST_INPUT: &str = "kh-tc\n\
                               qp-kh\n\
@@ -126,15 +304,88 @@ mod tests {
 
     #[test]
     fn test_p1() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part1(&gen);
         assert_eq!(res, 7);
+        assert_eq!(part1_t_first(&gen), res);
     }
 
     #[test]
     fn test_p2() {
-        let gen = generate(TEST_INPUT);
+        let gen = generate(TEST_INPUT).expect("valid input");
         let res = part2(&gen);
-        assert_eq!(res, "co,de,ka,ta".to_string());
+        assert_eq!(res, Some("co,de,ka,ta".to_string()));
+    }
+
+    #[test]
+    fn test_p2_greedy() {
+        let gen = generate("a-b\nb-c\nb-d\nc-d").expect("valid input");
+        assert_eq!(part2(&gen), Some("b,c,d".to_string()));
+        assert_eq!(part2_greedy(&gen), Some("c,d".to_string()));
+    }
+
+    /// The size of the largest clique, found by checking every set of computers.
+    fn reference_max_clique(conns: &HashMap<String, Vec<String>>) -> usize {
+        let names = conns.keys().collect_vec();
+
+        names
+            .iter()
+            .powerset()
+            .filter(|set| {
+                set.iter()
+                    .tuple_combinations()
+                    .all(|(a, b)| conns[**a].contains(b))
+            })
+            .map(|set| set.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn network() -> impl Strategy<Value = String> {
+        prop::collection::vec((0..8u8, 0..8u8), 1..24).prop_map(|edges| {
+            edges
+                .into_iter()
+                .filter(|(a, b)| a != b)
+                .map(|(a, b)| format!("c{a}-c{b}"))
+                .join("\n")
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_p2_reference(network in network()) {
+            prop_assume!(!network.is_empty());
+            let conns = generate(&network).expect("valid network");
+
+            let res = part2(&conns).expect("non-empty network");
+            let clique = res.split(',').collect_vec();
+
+            prop_assert!(clique
+                .iter()
+                .tuple_combinations()
+                .all(|(a, b)| conns[*a].iter().any(|c| c == b)));
+            prop_assert_eq!(clique.len(), reference_max_clique(&conns));
+        }
+    }
+
+    #[test]
+    fn test_graph() {
+        let gen = generate(TEST_INPUT).expect("valid input");
+        let graph = Day23
+            .graph(&gen, Part::Two, &PuzzleParams::new())
+            .expect("graph");
+        assert_eq!((graph.node_count(), graph.edge_count()), (16, 32));
+
+        let dot = graph.to_string();
+        for name in ["co", "de", "ka", "ta"] {
+            assert!(dot.contains(&format!("\"{name}\" [style=filled")));
+        }
+        assert_eq!(dot.matches("color=red").count(), 6);
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(23, Part::One, &parse::windows(TEST_INPUT));
+        assert_eq!(res, Ok(Answer::Int(7)));
     }
 }
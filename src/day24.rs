@@ -1,3 +1,9 @@
+use crate::budget::{self, Budget, Exhausted};
+use crate::dot::Graph;
+use crate::error::{Error, ParseError};
+use crate::params::PuzzleParams;
+use crate::parse::Source;
+use crate::solver::{Answer, Part, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
 use itertools::Itertools;
 use parse_display_derive::{Display, FromStr};
@@ -24,51 +30,73 @@ pub struct Operation {
     output: String,
 }
 
-impl Operation {
-    fn evaluate<'a>(
-        &'a self,
-        operations: &'a [Self],
-        values: &mut HashMap<&'a str, usize>,
-        eval_stack: &[&'a str],
-    ) -> Option<usize> {
-        if eval_stack.contains(&self.lhs.as_str()) || eval_stack.contains(&self.rhs.as_str()) {
-            return None;
+/// The wire values computed so far, working back from the wires asked for.
+struct Circuit<'a> {
+    gates: HashMap<&'a str, &'a Operation>,
+    /// `None` for wires found to have no value, so they are not tried again.
+    values: HashMap<&'a str, Option<usize>>,
+    /// Wires whose value is being computed, to detect cycles.
+    pending: HashSet<&'a str>,
+    budget: &'a Budget,
+}
+
+impl<'a> Circuit<'a> {
+    fn new(input: &'a Input, budget: &'a Budget) -> Self {
+        let mut gates = HashMap::new();
+        for op in &input.operations {
+            gates.entry(op.output.as_str()).or_insert(op);
         }
 
-        let lhs = values.get(self.lhs.as_str()).copied().or_else(|| {
-            let op = operations
-                .iter()
-                .find(|op| op.output == self.lhs)
-                .expect("exists");
-            let mut eval_stack = eval_stack.to_vec();
-            eval_stack.push(self.lhs.as_str());
-            op.evaluate(operations, values, &eval_stack)
-        });
-
-        let rhs = values.get(self.rhs.as_str()).copied().or_else(|| {
-            let op = operations
-                .iter()
-                .find(|op| op.output == self.rhs)
-                .expect("exists");
-            let mut eval_stack = eval_stack.to_vec();
-            eval_stack.push(self.rhs.as_str());
-            op.evaluate(operations, values, &eval_stack)
-        });
-
-        if let Some(lhs) = lhs {
-            if let Some(rhs) = rhs {
-                let result = match self.kind {
-                    Operator::And => lhs & rhs,
-                    Operator::Or => lhs | rhs,
-                    Operator::Xor => lhs ^ rhs,
-                };
-
-                values.entry(self.output.as_str()).insert_entry(result);
-                return Some(result);
-            }
+        let values = input
+            .initial_values
+            .iter()
+            .map(|(wire, &value)| (wire.as_str(), Some(value)))
+            .collect();
+
+        Self {
+            gates,
+            values,
+            pending: HashSet::new(),
+            budget,
+        }
+    }
+
+    /// `None` if the wire is not driven by anything or depends on itself.
+    ///
+    /// A wire that reaches a pending one lies on a cycle, so its `None` holds
+    /// whichever wire the evaluation started from.
+    fn wire(&mut self, wire: &'a str) -> Result<Option<usize>, Exhausted> {
+        if let Some(&value) = self.values.get(wire) {
+            return Ok(value);
+        }
+        let Some(&gate) = self.gates.get(wire) else {
+            self.values.insert(wire, None);
+            return Ok(None);
+        };
+        if !self.pending.insert(wire) {
+            return Ok(None);
         }
+        self.budget.step()?;
 
-        None
+        let value = self.gate(gate)?;
+        self.pending.remove(wire);
+        self.values.insert(wire, value);
+        Ok(value)
+    }
+
+    fn gate(&mut self, gate: &'a Operation) -> Result<Option<usize>, Exhausted> {
+        let Some(lhs) = self.wire(&gate.lhs)? else {
+            return Ok(None);
+        };
+        let Some(rhs) = self.wire(&gate.rhs)? else {
+            return Ok(None);
+        };
+
+        Ok(Some(match gate.kind {
+            Operator::And => lhs & rhs,
+            Operator::Or => lhs | rhs,
+            Operator::Xor => lhs ^ rhs,
+        }))
     }
 }
 
@@ -79,60 +107,61 @@ pub struct Input {
 }
 
 #[aoc_generator(day24)]
-pub fn generate(s: &str) -> Option<Input> {
-    let (initial, insts) = s.split_once("\n\n")?;
+pub fn generate(s: &str) -> Result<Input, ParseError> {
+    let src = Source::new(24, s);
+    let (initial, insts) = src.sections()?;
 
     let mut initial_values = HashMap::new();
     for line in initial.lines() {
-        let (name, value) = line.split_once(": ")?;
-        let value = value.parse::<usize>().ok()?;
+        let (name, value) = src.split_once(line, ": ")?;
+        let value = src.number::<usize>(value)?;
 
         initial_values.insert(name.to_string(), value);
     }
 
     let mut operations = vec![];
     for op in insts.lines() {
-        let inst = op.parse::<Operation>().ok()?;
+        let inst = op
+            .parse::<Operation>()
+            .map_err(|_| src.error(op, "expected `<wire> <AND|OR|XOR> <wire> -> <wire>`"))?;
         operations.push(inst);
     }
 
-    Some(Input {
+    Ok(Input {
         initial_values,
         operations,
     })
 }
 
-fn evaluate<'a>(
-    operations: &'a [Operation],
-    values: &mut HashMap<&'a str, usize>,
-) -> Option<usize> {
-    let z_wires = operations
-        .iter()
-        .filter(|op| op.output.starts_with('z'))
-        .sorted_by_key(|op| &op.output)
+/// The number on the `z` wires, or `None` if one of them has no value.
+fn evaluate(conns: &Input, budget: &Budget) -> Result<Option<usize>, Exhausted> {
+    let mut circuit = Circuit::new(conns, budget);
+    let z_wires = circuit
+        .gates
+        .keys()
+        .copied()
+        .filter(|wire| wire.starts_with('z'))
+        .sorted()
         .collect_vec();
 
     let mut result = 0;
-    for op in z_wires.iter().rev() {
-        let bit = op.evaluate(operations, values, &[])?;
+    for wire in z_wires.into_iter().rev() {
+        let Some(bit) = circuit.wire(wire)? else {
+            return Ok(None);
+        };
         result = (result << 1) | bit;
     }
 
-    Some(result)
+    Ok(Some(result))
 }
 
 #[aoc(day24, part1)]
 pub fn part1(conns: &Input) -> Option<usize> {
-    let mut values = HashMap::new();
-    for (k, v) in &conns.initial_values {
-        values.entry(k.as_str()).insert_entry(*v);
-    }
-
-    evaluate(&conns.operations, &mut values)
+    evaluate(conns, &Budget::unlimited()).ok().flatten()
 }
 
-#[aoc(day24, part2)]
-pub fn part2(conns: &Input) -> Option<String> {
+/// Outputs of gates that are wired differently than in a ripple-carry adder.
+fn swapped_outputs(conns: &Input) -> Option<HashSet<String>> {
     let mut invalid = HashSet::new();
 
     for op in &conns.operations {
@@ -169,14 +198,161 @@ pub fn part2(conns: &Input) -> Option<String> {
         }
     }
 
-    assert_eq!(invalid.len(), 8);
+    Some(invalid)
+}
+
+/// Bits of the adder that part 2 expects.
+const ADDER_BITS: usize = 45;
+
+/// Gate outputs that part 2 expects to be swapped.
+const SWAPPED_OUTPUTS: usize = 8;
+
+/// Ways in which `conns` is not a 45-bit adder with four pairs of swapped
+/// gate outputs.
+fn adder_violations(conns: &Input) -> Vec<String> {
+    let mut violations = vec![];
+
+    let names = |prefix: char, bits: usize| (0..bits).map(move |bit| format!("{prefix}{bit:02}"));
+    for prefix in ['x', 'y'] {
+        let found = (conns.initial_values.keys())
+            .filter(|wire| wire.starts_with(prefix))
+            .count();
+        let expected =
+            names(prefix, ADDER_BITS).all(|name| conns.initial_values.contains_key(&name));
+        if !expected || found != ADDER_BITS {
+            violations.push(format!(
+                "expected a {ADDER_BITS}-bit adder with inputs {prefix}00 to {prefix}{:02}, \
+                 found {found} `{prefix}` wires",
+                ADDER_BITS - 1
+            ));
+        }
+    }
+
+    let outputs = conns
+        .operations
+        .iter()
+        .map(|op| &op.output)
+        .collect::<HashSet<_>>();
+    let found = outputs.iter().filter(|wire| wire.starts_with('z')).count();
+    let expected = names('z', ADDER_BITS + 1).all(|name| outputs.contains(&name));
+    if !expected || found != ADDER_BITS + 1 {
+        violations.push(format!(
+            "expected a {ADDER_BITS}-bit adder with outputs z00 to z{ADDER_BITS:02}, \
+             found {found} `z` wires"
+        ));
+    }
+
+    let swapped = swapped_outputs(conns).unwrap_or_default();
+    if swapped.len() != SWAPPED_OUTPUTS {
+        violations.push(format!(
+            "expected {SWAPPED_OUTPUTS} swapped gate outputs, found {}: {}",
+            swapped.len(),
+            swapped.iter().sorted().join(",")
+        ));
+    }
+
+    violations
+}
+
+#[aoc(day24, part2)]
+pub fn part2(conns: &Input) -> Option<String> {
+    let invalid = swapped_outputs(conns)?;
+    if invalid.len() != SWAPPED_OUTPUTS {
+        return None;
+    }
 
     Some(invalid.iter().sorted().join(","))
 }
 
+pub struct Day24;
+
+impl Solver for Day24 {
+    const DAY: u8 = 24;
+
+    type Input = Input;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, input: &Self::Input) -> Answer {
+        part2(input).into()
+    }
+
+    fn part1_within(
+        &self,
+        input: &Self::Input,
+        _params: &PuzzleParams,
+        budget: &Budget,
+    ) -> Result<Answer, Error> {
+        budget::answer(Self::DAY, evaluate(input, budget))
+    }
+
+    /// Part 2 recognizes the swapped wires by how a ripple-carry adder of
+    /// a fixed size is built.
+    fn validate(&self, input: &Self::Input, part: Part) -> Vec<String> {
+        match part {
+            Part::One => vec![],
+            Part::Two => adder_violations(input),
+        }
+    }
+
+    /// Wires and the gates between them, highlighting the wires that carry
+    /// a one in part 1 and the swapped outputs in part 2.
+    fn graph(
+        &self,
+        input: &Self::Input,
+        part: Part,
+        _params: &PuzzleParams,
+    ) -> Result<Graph, Error> {
+        let mut graph = Graph::directed();
+        for (wire, _) in input.initial_values.iter().sorted() {
+            graph.node(wire, wire);
+        }
+        for op in &input.operations {
+            let gate = format!("{} {}", op.kind, op.output);
+            graph.node(&gate, op.kind);
+            graph.edge(&op.lhs, &gate);
+            graph.edge(&op.rhs, &gate);
+            graph.edge(&gate, &op.output);
+        }
+
+        let highlighted = match part {
+            Part::One => {
+                let budget = Budget::unlimited();
+                let mut circuit = Circuit::new(input, &budget);
+                let wires = circuit.gates.keys().copied().collect_vec();
+                for wire in wires {
+                    // unlimited, so this cannot fail
+                    let _ = circuit.wire(wire);
+                }
+                circuit
+                    .values
+                    .iter()
+                    .filter(|&(_, &value)| value == Some(1))
+                    .map(|(wire, _)| (*wire).to_string())
+                    .collect()
+            }
+            Part::Two => swapped_outputs(input).unwrap_or_default(),
+        };
+        for wire in highlighted {
+            graph.highlight_node(wire);
+        }
+
+        Ok(graph)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::gen;
+    use crate::parse;
+    use crate::solver::{self, Part};
 
     const TEST_INPUT_P1: &str = "x00: 1\n\
                                  x01: 1\n\
@@ -195,4 +371,49 @@ mod tests {
         let res = part1(&gen);
         assert_eq!(res, Some(4));
     }
+
+    #[test]
+    fn test_cycle() {
+        let input = "x00: 1\n\nx00 AND z01 -> z00\nx00 OR z00 -> z01";
+        let gen = generate(input).expect("valid input");
+        assert_eq!(part1(&gen), None);
+    }
+
+    #[test]
+    fn test_undriven_chain() {
+        // Each gate reads the one before it twice, down to a wire nothing drives.
+        let chain = (1..60)
+            .map(|i| format!("w{:02} XOR w{:02} -> w{i:02}", i - 1, i - 1))
+            .join("\n");
+        let input = format!("x00: 1\n\nx00 AND u00 -> w00\n{chain}\nx00 OR w59 -> z00");
+
+        let gen = generate(&input).expect("valid input");
+        assert_eq!(part1(&gen), None);
+        assert!(Day24.graph(&gen, Part::One, &PuzzleParams::new()).is_ok());
+    }
+
+    #[test]
+    fn test_validate() {
+        let gen = generate(TEST_INPUT_P1).expect("valid input");
+        assert!(Day24.validate(&gen, Part::One).is_empty());
+        assert_eq!(
+            Day24.validate(&gen, Part::Two),
+            [
+                "expected a 45-bit adder with inputs x00 to x44, found 3 `x` wires",
+                "expected a 45-bit adder with inputs y00 to y44, found 3 `y` wires",
+                "expected a 45-bit adder with outputs z00 to z45, found 3 `z` wires",
+                "expected 8 swapped gate outputs, found 2: z00,z02",
+            ]
+        );
+
+        let input = gen::generate(24, 45, 0).expect("day 24 exists");
+        let gen = generate(&input).expect("valid input");
+        assert!(Day24.validate(&gen, Part::Two).is_empty());
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(24, Part::One, &parse::windows(TEST_INPUT_P1));
+        assert_eq!(res, Ok(Answer::Int(4)));
+    }
 }
@@ -1,23 +1,30 @@
+use crate::error::{Error, ParseError};
+use crate::grid::{self, Grid};
+use crate::par;
+use crate::parse::Source;
+use crate::solver::{Answer, Part, Solver};
 use aoc_runner_derive::{aoc, aoc_generator};
-use itertools::{iproduct, Itertools};
-use pathfinding::matrix::Matrix;
+use itertools::iproduct;
 
 #[aoc_generator(day25)]
-pub fn generate(s: &str) -> Option<Vec<Matrix<char>>> {
-    let mut result = vec![];
+pub fn generate(s: &str) -> Result<Vec<Grid<char>>, ParseError> {
+    let src = Source::new(25, s);
+    let mut schematics: Vec<Grid<char>> = vec![];
 
-    for g in s.split("\n\n") {
-        let v = g.lines().map(|l| l.chars().collect_vec()).collect_vec();
-        result.push(Matrix::from_rows(v).ok()?);
+    for text in s.split("\n\n") {
+        let grid = grid::parse(&src, text)?;
+        if let Some(first) = schematics.first() {
+            if (first.rows, first.columns) != (grid.rows, grid.columns) {
+                return Err(src.error(text, "expected all schematics to have the same size"));
+            }
+        }
+        schematics.push(grid);
     }
 
-    Some(result)
+    Ok(schematics)
 }
 
-fn has_overlap(lock: &Matrix<char>, key: &Matrix<char>) -> bool {
-    assert_eq!(lock.rows, key.rows);
-    assert_eq!(lock.columns, key.columns);
-
+fn has_overlap(lock: &Grid<char>, key: &Grid<char>) -> bool {
     for (r, c) in iproduct!(0..lock.rows, 0..lock.columns) {
         if lock[(r, c)] == '#' && lock[(r, c)] == key[(r, c)] {
             return true;
@@ -27,27 +34,71 @@ fn has_overlap(lock: &Matrix<char>, key: &Matrix<char>) -> bool {
     false
 }
 
-#[aoc(day25, part1)]
-pub fn part1(conns: &[Matrix<char>]) -> usize {
-    let mut result = 0;
+/// Part 1 tells locks from keys by the top-left tile alone.
+fn shape_violations(schematics: &[Grid<char>]) -> Vec<String> {
+    let filled = |grid: &Grid<char>, r: usize| (0..grid.columns).all(|c| grid[(r, c)] == '#');
+    let empty = |grid: &Grid<char>, r: usize| (0..grid.columns).all(|c| grid[(r, c)] == '.');
 
-    let (locks, keys): (Vec<Matrix<char>>, Vec<Matrix<char>>) =
+    schematics
+        .iter()
+        .enumerate()
+        .filter(|(_, grid)| {
+            let bottom = grid.rows - 1;
+            let lock = filled(grid, 0) && empty(grid, bottom);
+            let key = empty(grid, 0) && filled(grid, bottom);
+            !lock && !key
+        })
+        .map(|(i, _)| {
+            format!(
+                "schematic {} is neither a lock (a full top row over an empty bottom row) nor a key",
+                i + 1
+            )
+        })
+        .collect()
+}
+
+#[aoc(day25, part1)]
+pub fn part1(conns: &[Grid<char>]) -> usize {
+    let (locks, keys): (Vec<Grid<char>>, Vec<Grid<char>>) =
         conns.iter().cloned().partition(|it| it[(0, 0)] == '#');
 
-    for lock in &locks {
-        for key in &keys {
-            if !has_overlap(lock, key) {
-                result += 1;
-            }
-        }
+    par::sum(&locks, |lock| {
+        keys.iter().filter(|key| !has_overlap(lock, key)).count()
+    })
+}
+
+pub struct Day25;
+
+impl Solver for Day25 {
+    const DAY: u8 = 25;
+
+    type Input = Vec<Grid<char>>;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error> {
+        Ok(generate(input)?)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer {
+        part1(input).into()
+    }
+
+    fn part2(&self, _input: &Self::Input) -> Answer {
+        Answer::None
     }
 
-    result
+    fn validate(&self, input: &Self::Input, part: Part) -> Vec<String> {
+        match part {
+            Part::One => shape_violations(input),
+            Part::Two => vec![],
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parse;
+    use crate::solver;
 
     const TEST_INPUT_P1: &str = "#####\n\
                                  .####\n\
@@ -95,4 +146,30 @@ mod tests {
         let res = part1(&gen);
         assert_eq!(res, 3);
     }
+
+    #[test]
+    fn test_parse_error() {
+        let err = generate("##\n..\n\n...\n###").expect_err("mismatched sizes");
+        assert_eq!((err.line, err.column), (4, 1));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(solver::validate(25, Part::One, TEST_INPUT_P1), Ok(vec![]));
+
+        let res = solver::validate(25, Part::One, "#####\n#.#.#\n.....\n\n#####\n#.#.#\n#....");
+        assert_eq!(
+            res,
+            Ok(vec![
+                "schematic 2 is neither a lock (a full top row over an empty bottom row) nor a key"
+                    .to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_crlf() {
+        let res = solver::solve(25, Part::One, &parse::windows(TEST_INPUT_P1));
+        assert_eq!(res, Ok(Answer::Int(3)));
+    }
 }
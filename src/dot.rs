@@ -0,0 +1,204 @@
+//! Graphviz DOT output for the days whose puzzles are graphs.
+
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Clone, Debug)]
+struct Node {
+    id: String,
+    label: Option<String>,
+    highlighted: bool,
+}
+
+#[derive(Clone, Debug)]
+struct Edge {
+    from: String,
+    to: String,
+    label: Option<String>,
+    highlighted: bool,
+}
+
+/// A graph to render as DOT, with a subset of nodes and edges highlighted,
+/// usually the part of the graph that makes up an answer.
+///
+/// Nodes and edges are written in the order they were first added.
+#[derive(Clone, Debug)]
+pub struct Graph {
+    directed: bool,
+    nodes: Vec<Node>,
+    node_index: HashMap<String, usize>,
+    edges: Vec<Edge>,
+    edge_index: HashMap<(String, String), usize>,
+}
+
+#[allow(clippy::needless_pass_by_value)]
+impl Graph {
+    fn new(directed: bool) -> Self {
+        Self {
+            directed,
+            nodes: vec![],
+            node_index: HashMap::new(),
+            edges: vec![],
+            edge_index: HashMap::new(),
+        }
+    }
+
+    pub fn directed() -> Self {
+        Self::new(true)
+    }
+
+    pub fn undirected() -> Self {
+        Self::new(false)
+    }
+
+    fn node_mut(&mut self, id: String) -> &mut Node {
+        let idx = *self.node_index.entry(id.clone()).or_insert_with(|| {
+            self.nodes.push(Node {
+                id,
+                label: None,
+                highlighted: false,
+            });
+            self.nodes.len() - 1
+        });
+        &mut self.nodes[idx]
+    }
+
+    fn edge_key(&self, from: String, to: String) -> (String, String) {
+        if self.directed || from <= to {
+            (from, to)
+        } else {
+            (to, from)
+        }
+    }
+
+    fn edge_mut(&mut self, from: String, to: String) -> &mut Edge {
+        self.node_mut(from.clone());
+        self.node_mut(to.clone());
+
+        let key = self.edge_key(from, to);
+        let idx = *self.edge_index.entry(key.clone()).or_insert_with(|| {
+            self.edges.push(Edge {
+                from: key.0,
+                to: key.1,
+                label: None,
+                highlighted: false,
+            });
+            self.edges.len() - 1
+        });
+        &mut self.edges[idx]
+    }
+
+    /// Adds a node, or relabels it if it exists.
+    pub fn node(&mut self, id: impl ToString, label: impl ToString) {
+        self.node_mut(id.to_string()).label = Some(label.to_string());
+    }
+
+    /// Adds an edge and any missing end points. Undirected edges are only
+    /// added once, whatever the order of their end points.
+    pub fn edge(&mut self, from: impl ToString, to: impl ToString) {
+        self.edge_mut(from.to_string(), to.to_string());
+    }
+
+    pub fn labeled_edge(&mut self, from: impl ToString, to: impl ToString, label: impl ToString) {
+        self.edge_mut(from.to_string(), to.to_string()).label = Some(label.to_string());
+    }
+
+    pub fn highlight_node(&mut self, id: impl ToString) {
+        self.node_mut(id.to_string()).highlighted = true;
+    }
+
+    /// Highlights an edge, adding it if it is not part of the graph yet.
+    pub fn highlight_edge(&mut self, from: impl ToString, to: impl ToString) {
+        self.edge_mut(from.to_string(), to.to_string()).highlighted = true;
+    }
+
+    pub const fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub const fn edge_count(&self) -> usize {
+        self.edges.len()
+    }
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn attributes(label: Option<&String>, highlight: Option<&str>) -> String {
+    let attrs = label
+        .map(|label| format!("label={}", quote(label)))
+        .into_iter()
+        .chain(highlight.map(ToString::to_string))
+        .collect::<Vec<_>>();
+
+    if attrs.is_empty() {
+        String::new()
+    } else {
+        format!(" [{}]", attrs.join(", "))
+    }
+}
+
+impl fmt::Display for Graph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (kind, arrow) = if self.directed {
+            ("digraph", "->")
+        } else {
+            ("graph", "--")
+        };
+
+        writeln!(f, "{kind} {{")?;
+        for node in &self.nodes {
+            let highlight = node.highlighted.then_some("style=filled, fillcolor=gold");
+            writeln!(
+                f,
+                "    {}{};",
+                quote(&node.id),
+                attributes(node.label.as_ref(), highlight)
+            )?;
+        }
+        for edge in &self.edges {
+            let highlight = edge.highlighted.then_some("color=red, penwidth=2");
+            writeln!(
+                f,
+                "    {} {arrow} {}{};",
+                quote(&edge.from),
+                quote(&edge.to),
+                attributes(edge.label.as_ref(), highlight)
+            )?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot() {
+        let mut graph = Graph::undirected();
+        graph.node("a", "A \"1\"");
+        graph.edge("b", "a");
+        graph.labeled_edge("a", "b", 3);
+        graph.highlight_edge("b", "c");
+        graph.highlight_node("c");
+
+        assert_eq!((graph.node_count(), graph.edge_count()), (3, 2));
+        assert_eq!(
+            graph.to_string(),
+            "graph {\n    \
+                 \"a\" [label=\"A \\\"1\\\"\"];\n    \
+                 \"b\";\n    \
+                 \"c\" [style=filled, fillcolor=gold];\n    \
+                 \"a\" -- \"b\" [label=\"3\"];\n    \
+                 \"b\" -- \"c\" [color=red, penwidth=2];\n\
+             }\n"
+        );
+
+        let mut graph = Graph::directed();
+        graph.edge(2, 1);
+        graph.edge(1, 2);
+        assert_eq!(graph.edge_count(), 2);
+    }
+}
@@ -0,0 +1,171 @@
+use crate::budget::Exhausted;
+use std::fmt;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    UnknownDay(u8),
+    UnknownPart(u8),
+    Parse(ParseError),
+    InputMismatch { day: u8 },
+    CacheMismatch { day: u8 },
+    UnknownParam { day: u8, key: String },
+    InvalidParam { key: String, value: String },
+    Io { path: String, message: String },
+    InvalidAnswers(String),
+    InvalidBaseline(String),
+    NoVisualization { day: u8 },
+    Panic { day: u8, message: String },
+    Overflow { day: u8 },
+    GaveUp { day: u8, reason: Exhausted },
+    NoGraph { day: u8 },
+    UnknownVariant { day: u8, name: String },
+}
+
+impl Error {
+    pub fn io(path: &Path, error: &std::io::Error) -> Self {
+        Self::Io {
+            path: path.display().to_string(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownDay(day) => write!(f, "no solver registered for day {day}"),
+            Self::UnknownPart(part) => write!(f, "part must be 1 or 2, got {part}"),
+            Self::Parse(e) => write!(f, "{e}"),
+            Self::InputMismatch { day } => {
+                write!(f, "day {day}: parsed input belongs to a different solver")
+            }
+            Self::CacheMismatch { day } => {
+                write!(f, "day {day}: cache belongs to a different solver")
+            }
+            Self::UnknownParam { day, key } => write!(f, "day {day} has no parameter `{key}`"),
+            Self::InvalidParam { key, value } => {
+                write!(f, "invalid value `{value}` for parameter `{key}`")
+            }
+            Self::Io { path, message } => write!(f, "{path}: {message}"),
+            Self::InvalidAnswers(message) => write!(f, "invalid answers file: {message}"),
+            Self::InvalidBaseline(message) => write!(f, "invalid benchmark baseline: {message}"),
+            Self::NoVisualization { day } => write!(f, "day {day} has no visualization"),
+            Self::Panic { day, message } => write!(f, "day {day} panicked: {message}"),
+            Self::Overflow { day } => write!(f, "day {day}: arithmetic overflow"),
+            Self::GaveUp { day, reason } => write!(f, "day {day} gave up: {reason}"),
+            Self::NoGraph { day } => write!(f, "day {day} has no graph"),
+            Self::UnknownVariant { day, name } => {
+                write!(f, "day {day} has no variant named `{name}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ParseError> for Error {
+    fn from(value: ParseError) -> Self {
+        Self::Parse(value)
+    }
+}
+
+/// A malformed puzzle input, pointing at the offending text.
+///
+/// `line` and `column` are 1-based and count characters, not bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub text: String,
+    pub message: String,
+    /// Whether `text` is empty because the input ended.
+    pub at_end: bool,
+}
+
+impl ParseError {
+    /// Builds an error for `span`, which is expected to be a subslice of `input`.
+    ///
+    /// A `span` from elsewhere is reported at the end of the input.
+    pub fn at(day: u8, input: &str, span: &str, message: impl Into<String>) -> Self {
+        let offset = (span.as_ptr() as usize)
+            .checked_sub(input.as_ptr() as usize)
+            .filter(|&offset| offset <= input.len() && input.is_char_boundary(offset))
+            .unwrap_or(input.len());
+
+        let before = &input[..offset];
+        let line_start = before.rfind('\n').map_or(0, |idx| idx + 1);
+
+        Self {
+            day,
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            text: span.to_string(),
+            message: message.into(),
+            at_end: offset == input.len(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day {}, line {}, column {}: {}",
+            self.day, self.line, self.column, self.message
+        )?;
+
+        if self.text.is_empty() && self.at_end {
+            write!(f, " (at end of input)")
+        } else if self.text.is_empty() {
+            write!(f, " (found nothing)")
+        } else {
+            write!(f, " (found `{}`)", self.text)
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position() {
+        let input = "12 34\n56 x8\n";
+        let err = ParseError::at(1, input, &input[9..10], "expected a number");
+        assert_eq!((err.line, err.column), (2, 4));
+        assert_eq!(err.text, "x");
+        assert_eq!(
+            err.to_string(),
+            "day 1, line 2, column 4: expected a number (found `x`)"
+        );
+    }
+
+    #[test]
+    fn test_foreign_span() {
+        let input = "ab\ncd";
+        let other = String::from("ef");
+        let err = ParseError::at(1, input, &other, "unexpected text");
+        assert_eq!((err.line, err.column), (2, 3));
+        assert!(err.at_end);
+    }
+
+    #[test]
+    fn test_empty_span() {
+        let input = "1,,2";
+        let err = ParseError::at(1, input, &input[2..2], "expected a number");
+        assert_eq!(
+            err.to_string(),
+            "day 1, line 1, column 3: expected a number (found nothing)"
+        );
+
+        let err = ParseError::at(1, input, &input[4..], "expected `,`");
+        assert_eq!(
+            err.to_string(),
+            "day 1, line 1, column 5: expected `,` (at end of input)"
+        );
+    }
+}
@@ -0,0 +1,147 @@
+//! Feeds malformed inputs to every generator and solver, failing on any panic.
+//!
+//! The generators see each input both as given, `\r`, BOM and all, and
+//! normalized the way the runner hands it to them; the solvers only see the
+//! latter, as they do in the runner.
+//!
+//! Run more cases with e.g. `PROPTEST_CASES=100000 cargo test fuzz`.
+
+use crate::budget::Budget;
+use crate::gen;
+use crate::params::PuzzleParams;
+use crate::solver::{self, Part};
+use proptest::prelude::*;
+
+/// Characters that make up the puzzle inputs.
+const ALPHABET: &str = "0123456789 \n\r\t\u{feff}.#,:|-=+SEAOX^v<>@[]pvxyzBCRegistrProgamuwbt";
+
+/// Steps each part may take, as a malformed input can keep an iterating
+/// solver going forever.
+const MAX_STEPS: u64 = 1 << 20;
+
+/// Steps on the full-size grids of the simulation days, which are slow enough
+/// per step that a case would otherwise take seconds.
+const MAX_STEPS_FULL_SIZE: u64 = 1 << 6;
+
+/// The params to solve with and the steps each may take: the defaults, plus
+/// smaller grids for the simulation days.
+fn params(day: u8) -> Vec<(PuzzleParams, u64)> {
+    let small = match day {
+        14 => PuzzleParams::new().with("width", 11).with("height", 7),
+        18 => PuzzleParams::new().with("size", 7).with("bytes", 12),
+        _ => return vec![(PuzzleParams::new(), MAX_STEPS)],
+    };
+    vec![
+        (PuzzleParams::new(), MAX_STEPS_FULL_SIZE),
+        (small, MAX_STEPS),
+    ]
+}
+
+/// Parses both parts, raw and normalized, and solves the normalized ones
+/// with each of the day's params; all of which must return instead of
+/// panicking.
+fn run(day: u8, input: &str) {
+    let solver = solver::solver(day).expect("registered day");
+
+    for part in Part::ALL {
+        let _ = solver.parse_raw(part, input);
+        if let Ok(parsed) = solver.parse(part, input) {
+            for (params, max_steps) in params(day) {
+                let budget = Budget::with_max_steps(max_steps);
+                let _ = solver.solve_within(part, &parsed, &params, &budget);
+            }
+        }
+    }
+}
+
+/// Dresses `input` up the way editors and downloads do.
+fn decorate(input: &str, bom: bool, crlf: bool, trailing: &str) -> String {
+    let newline = if crlf { "\r\n" } else { "\n" };
+    let lines = input.lines().map(|line| format!("{line}{trailing}"));
+    let body = lines.collect::<Vec<_>>().join(newline);
+    format!("{}{body}{newline}", if bom { "\u{feff}" } else { "" })
+}
+
+#[derive(Clone, Debug)]
+enum Mutation {
+    Delete { at: usize, len: usize },
+    Duplicate { at: usize, len: usize },
+    Insert { at: usize, c: char },
+    Replace { at: usize, c: char },
+}
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+    let c = proptest::sample::select(ALPHABET.chars().collect::<Vec<_>>());
+    prop_oneof![
+        (any::<usize>(), 1..8usize).prop_map(|(at, len)| Mutation::Delete { at, len }),
+        (any::<usize>(), 1..8usize).prop_map(|(at, len)| Mutation::Duplicate { at, len }),
+        (any::<usize>(), c.clone()).prop_map(|(at, c)| Mutation::Insert { at, c }),
+        (any::<usize>(), c).prop_map(|(at, c)| Mutation::Replace { at, c }),
+    ]
+}
+
+/// Applies `mutations` to `input`; positions wrap around its length.
+fn mutate(input: &str, mutations: &[Mutation]) -> String {
+    let mut chars = input.chars().collect::<Vec<_>>();
+
+    for mutation in mutations {
+        let len = chars.len().max(1);
+        match *mutation {
+            Mutation::Delete { at, len: n } => {
+                let at = (at % len).min(chars.len());
+                chars.drain(at..(at + n).min(chars.len()));
+            }
+            Mutation::Duplicate { at, len: n } => {
+                let at = (at % len).min(chars.len());
+                let span = chars[at..(at + n).min(chars.len())].to_vec();
+                chars.splice(at..at, span);
+            }
+            Mutation::Insert { at, c } => chars.insert((at % len).min(chars.len()), c),
+            Mutation::Replace { at, c } => {
+                if let Some(old) = chars.get_mut(at % len) {
+                    *old = c;
+                }
+            }
+        }
+    }
+
+    chars.into_iter().collect()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(512))]
+
+    #[test]
+    fn test_bytes(day in 1..=25u8, bytes in prop::collection::vec(any::<u8>(), 0..200)) {
+        run(day, &String::from_utf8_lossy(&bytes));
+    }
+
+    #[test]
+    fn test_tokens(day in 1..=25u8, input in prop::collection::vec(prop::sample::select(ALPHABET.chars().collect::<Vec<_>>()), 0..200)) {
+        run(day, &input.into_iter().collect::<String>());
+    }
+
+    #[test]
+    fn test_mutated(
+        day in 1..=25u8,
+        size in 1..12usize,
+        seed: u64,
+        mutations in prop::collection::vec(mutation(), 0..4),
+    ) {
+        let input = gen::generate(day, size, seed).expect("registered day");
+        run(day, &mutate(&input, &mutations));
+    }
+
+    #[test]
+    fn test_decorated(
+        day in 1..=25u8,
+        size in 1..12usize,
+        seed: u64,
+        bom: bool,
+        crlf: bool,
+        trailing in "[ \t]{0,2}",
+    ) {
+        let input = gen::generate(day, size, seed).expect("registered day");
+        run(day, &decorate(&input, bom, crlf, &trailing));
+    }
+}
@@ -0,0 +1,829 @@
+use crate::error::Error;
+use crate::solver;
+use itertools::Itertools;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+/// The size of a real puzzle input for `day`, in the unit [`generate`] uses.
+pub const fn default_size(day: u8) -> usize {
+    match day {
+        1 | 2 => 1000,
+        3 => 700,
+        4 | 12 => 140,
+        5 => 200,
+        6 => 130,
+        7 => 850,
+        8 | 10 | 15 => 50,
+        9 => 10_000,
+        11 => 8,
+        13 => 320,
+
+        16 | 20 => 141,
+        17 => 16,
+        18 => 71,
+        19 => 400,
+        21 => 5,
+        22 => 2000,
+        23 => 520,
+        24 => 45,
+        14 | 25 => 500,
+        _ => 0,
+    }
+}
+
+/// Generates a random input for `day` that its generator accepts.
+///
+/// The same `seed` always produces the same input. What `size` counts depends
+/// on the day:
+///
+/// - grid side length: days 4, 6, 8, 10, 12, 15, 16, 18 and 20
+/// - lines or records: days 1, 2, 5, 7, 13, 14, 21, 22 and 25
+/// - instructions: day 3; files: day 9; stones: day 11
+/// - octal digits of register A: day 17
+/// - towels and designs: day 19; computers: day 23; adder bits: day 24
+pub fn generate(day: u8, size: usize, seed: u64) -> Result<String, Error> {
+    solver::solver(day)?;
+    if size == 0 {
+        return Err(Error::InvalidParam {
+            key: "size".to_string(),
+            value: size.to_string(),
+        });
+    }
+
+    let rng = &mut StdRng::seed_from_u64(seed);
+    let input = match day {
+        1 => day01(rng, size),
+        2 => day02(rng, size),
+        3 => day03(rng, size),
+        4 => day04(rng, size),
+        5 => day05(rng, size),
+        6 => day06(rng, size),
+        7 => day07(rng, size),
+        8 => day08(rng, size),
+        9 => day09(rng, size),
+        10 => day10(rng, size),
+        11 => day11(rng, size),
+        12 => day12(rng, size),
+        13 => day13(rng, size),
+        14 => day14(rng, size),
+        15 => day15(rng, size),
+        16 => day16(rng, size),
+        17 => day17(rng, size),
+        18 => day18(rng, size),
+        19 => day19(rng, size),
+        20 => day20(rng, size),
+        21 => day21(rng, size),
+        22 => day22(rng, size),
+        23 => day23(rng, size),
+        24 => day24(rng, size),
+        _ => day25(rng, size),
+    };
+
+    Ok(input)
+}
+
+fn pick(rng: &mut StdRng, chars: &str) -> char {
+    chars
+        .chars()
+        .nth(rng.gen_range(0..chars.chars().count()))
+        .unwrap_or(' ')
+}
+
+fn render(grid: &[Vec<char>]) -> String {
+    grid.iter()
+        .map(|row| row.iter().collect::<String>())
+        .join("\n")
+}
+
+fn day01(rng: &mut StdRng, size: usize) -> String {
+    let left = (0..size)
+        .map(|_| rng.gen_range(10_000..100_000))
+        .collect_vec();
+
+    left.iter()
+        .map(|l| {
+            // reuse some numbers so that the similarity score is not zero
+            let r = if rng.gen_bool(0.3) {
+                left[rng.gen_range(0..size)]
+            } else {
+                rng.gen_range(10_000..100_000)
+            };
+            format!("{l}   {r}")
+        })
+        .join("\n")
+}
+
+fn day02(rng: &mut StdRng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let sign = if rng.gen_bool(0.5) { 1 } else { -1 };
+            let mut level: i32 = rng.gen_range(50..=60);
+            let mut levels = vec![level];
+
+            for _ in 1..rng.gen_range(5..=8) {
+                let step = if rng.gen_bool(0.1) {
+                    rng.gen_range(-7..=7)
+                } else {
+                    sign * rng.gen_range(1..=3)
+                };
+                level += step;
+                levels.push(level);
+            }
+
+            levels.iter().join(" ")
+        })
+        .join("\n")
+}
+
+fn day03(rng: &mut StdRng, size: usize) -> String {
+    const NOISE: &str = "mul()don't[]{}<>%$#@!^&*?:;,' 0123456789from/select+-";
+    let per_line = size.div_ceil(6);
+
+    let mut memory = String::new();
+    for i in 0..size {
+        if i > 0 && i % per_line == 0 {
+            memory.push('\n');
+        }
+
+        for _ in 0..rng.gen_range(0..8) {
+            memory.push(pick(rng, NOISE));
+        }
+
+        let (a, b) = (rng.gen_range(1..1000), rng.gen_range(1..1000));
+        let instruction = match rng.gen_range(0..100) {
+            0..80 => format!("mul({a},{b})"),
+            80..87 => "do()".to_string(),
+            87..94 => "don't()".to_string(),
+            94..97 => format!("mul({a}*"),
+            97..98 => format!("mul ( {a},{b} )"),
+            98..99 => format!("mul[{a},{b}]"),
+            _ => format!("mul({}{a},{b})", rng.gen_range(1..10)),
+        };
+        memory.push_str(&instruction);
+    }
+
+    memory
+}
+
+fn day04(rng: &mut StdRng, size: usize) -> String {
+    let mut grid = vec![vec![' '; size]; size];
+    for tile in grid.iter_mut().flatten() {
+        *tile = pick(rng, "XMAS");
+    }
+
+    // random letters rarely spell the word, so plant a few copies
+    for _ in 0..size * size / 20 {
+        let (dr, dc): (isize, isize) = (rng.gen_range(-1..=1), rng.gen_range(-1..=1));
+        let (r, c) = (rng.gen_range(0..size), rng.gen_range(0..size));
+        let cells = (0..4)
+            .map(|i| {
+                let r = r.checked_add_signed(dr * i).filter(|&r| r < size)?;
+                let c = c.checked_add_signed(dc * i).filter(|&c| c < size)?;
+                Some((r, c))
+            })
+            .collect::<Option<Vec<_>>>();
+
+        if let Some(cells) = cells.filter(|_| (dr, dc) != (0, 0)) {
+            for ((r, c), letter) in cells.into_iter().zip("XMAS".chars()) {
+                grid[r][c] = letter;
+            }
+        }
+    }
+
+    render(&grid)
+}
+
+fn day05(rng: &mut StdRng, size: usize) -> String {
+    let mut pages = (10..100).collect_vec();
+    pages.shuffle(rng);
+    pages.truncate(49);
+
+    // a rule for every pair of pages keeps each update sortable
+    let mut rules = pages
+        .iter()
+        .tuple_combinations()
+        .map(|(lhs, rhs)| format!("{lhs}|{rhs}"))
+        .collect_vec();
+    rules.shuffle(rng);
+
+    let updates = (0..size)
+        .map(|_| {
+            let len = 2 * rng.gen_range(2..=11) + 1;
+            let mut update = pages.choose_multiple(rng, len).copied().collect_vec();
+            if rng.gen_bool(0.5) {
+                update.sort_by_key(|page| pages.iter().position(|p| p == page));
+            }
+            update.iter().join(",")
+        })
+        .join("\n");
+
+    format!("{}\n\n{updates}", rules.join("\n"))
+}
+
+/// Whether a guard starting at `start` walks off `grid` instead of looping.
+fn guard_escapes(grid: &[Vec<char>], start: (usize, usize)) -> bool {
+    let deltas = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+    let (mut pos, mut dir) = (start, 0);
+    let mut seen = HashSet::new();
+
+    while seen.insert((pos, dir)) {
+        let (dr, dc) = deltas[dir];
+        let next = pos
+            .0
+            .checked_add_signed(dr)
+            .zip(pos.1.checked_add_signed(dc))
+            .filter(|&(r, c)| r < grid.len() && c < grid[r].len());
+
+        match next {
+            None => return true,
+            Some((r, c)) if grid[r][c] == '#' => dir = (dir + 1) % 4,
+            Some(next) => pos = next,
+        }
+    }
+
+    false
+}
+
+fn day06(rng: &mut StdRng, size: usize) -> String {
+    loop {
+        let mut grid = vec![vec!['.'; size]; size];
+        for tile in grid.iter_mut().flatten() {
+            if rng.gen_bool(0.1) {
+                *tile = '#';
+            }
+        }
+
+        let start = (rng.gen_range(0..size), rng.gen_range(0..size));
+        grid[start.0][start.1] = '^';
+
+        if guard_escapes(&grid, start) {
+            return render(&grid);
+        }
+    }
+}
+
+fn day07(rng: &mut StdRng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let numbers = (0..rng.gen_range(2..=8))
+                .map(|_| rng.gen_range(1..100))
+                .collect_vec();
+
+            let mut target: i64 = numbers[0];
+            for &n in &numbers[1..] {
+                target = match rng.gen_range(0..3) {
+                    0 => target + n,
+                    1 => target * n,
+                    _ => target * 10i64.pow(n.ilog10() + 1) + n,
+                };
+            }
+            if rng.gen_bool(0.4) {
+                target += rng.gen_range(1..10);
+            }
+
+            format!("{target}: {}", numbers.iter().join(" "))
+        })
+        .join("\n")
+}
+
+fn day08(rng: &mut StdRng, size: usize) -> String {
+    const FREQUENCIES: &str = "0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+    let mut grid = vec![vec!['.'; size]; size];
+    for tile in grid.iter_mut().flatten() {
+        if rng.gen_bool(0.08) {
+            *tile = pick(rng, FREQUENCIES);
+        }
+    }
+
+    render(&grid)
+}
+
+fn day09(rng: &mut StdRng, size: usize) -> String {
+    let files = size.max(2);
+
+    let mut disk = String::new();
+    for i in 0..files {
+        disk.push(char::from(b'0' + rng.gen_range(1..=9)));
+        if i + 1 < files {
+            // compacting needs at least some free space
+            let min_free = u8::from(i == 0);
+            disk.push(char::from(b'0' + rng.gen_range(min_free..=9)));
+        }
+    }
+
+    disk
+}
+
+fn day10(rng: &mut StdRng, size: usize) -> String {
+    let mut grid = vec![vec![' '; size]; size];
+    for tile in grid.iter_mut().flatten() {
+        *tile = pick(rng, "0123456789");
+    }
+
+    // random heights rarely form trails, so walk a few uphill
+    for _ in 0..=size * size / 40 {
+        let (mut r, mut c) = (rng.gen_range(0..size), rng.gen_range(0..size));
+        for height in "0123456789".chars() {
+            grid[r][c] = height;
+
+            let next = [(-1, 0), (0, 1), (1, 0), (0, -1)]
+                .into_iter()
+                .filter_map(|(dr, dc)| Some((r.checked_add_signed(dr)?, c.checked_add_signed(dc)?)))
+                .filter(|&(r, c)| r < size && c < size)
+                .collect_vec();
+            if let Some(&next) = next.choose(rng) {
+                (r, c) = next;
+            }
+        }
+    }
+
+    render(&grid)
+}
+
+fn day11(rng: &mut StdRng, size: usize) -> String {
+    (0..size).map(|_| rng.gen_range(0..10_000_000)).join(" ")
+}
+
+fn day12(rng: &mut StdRng, size: usize) -> String {
+    let mut grid = vec![vec![' '; size]; size];
+
+    // copying a neighbor most of the time grows regions of one plant
+    for r in 0..size {
+        for c in 0..size {
+            grid[r][c] = match rng.gen_range(0..20) {
+                0..9 if c > 0 => grid[r][c - 1],
+                9..18 if r > 0 => grid[r - 1][c],
+                _ => pick(rng, "ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+            };
+        }
+    }
+
+    render(&grid)
+}
+
+fn day13(rng: &mut StdRng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let (a, b) = loop {
+                let a: (u32, u32) = (rng.gen_range(10..100), rng.gen_range(10..100));
+                let b: (u32, u32) = (rng.gen_range(10..100), rng.gen_range(10..100));
+                if a.0 * b.1 != a.1 * b.0 {
+                    break (a, b);
+                }
+            };
+
+            let prize = if rng.gen_bool(0.5) {
+                let (na, nb) = (rng.gen_range(0..=100), rng.gen_range(0..=100));
+                (na * a.0 + nb * b.0, na * a.1 + nb * b.1)
+            } else {
+                (rng.gen_range(1000..20_000), rng.gen_range(1000..20_000))
+            };
+
+            format!(
+                "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}",
+                a.0, a.1, b.0, b.1, prize.0, prize.1
+            )
+        })
+        .join("\n\n")
+}
+
+fn day14(rng: &mut StdRng, size: usize) -> String {
+    const WIDTH: i32 = 101;
+    const HEIGHT: i32 = 103;
+    const TREE: i32 = 10;
+
+    // with enough robots, make them form a filled square at some second
+    let second = rng.gen_range(1..WIDTH * HEIGHT);
+    let corner = (
+        rng.gen_range(0..WIDTH - TREE),
+        rng.gen_range(0..HEIGHT - TREE),
+    );
+    let tree = (0..TREE).cartesian_product(0..TREE).collect_vec();
+    let planted = if size >= tree.len() { tree.len() } else { 0 };
+
+    (0..size)
+        .map(|i| {
+            let (vx, vy) = (rng.gen_range(-99..100), rng.gen_range(-99..100));
+            let (px, py) = if i < planted {
+                let (dx, dy) = tree[i];
+                (
+                    (corner.0 + dx - vx * second).rem_euclid(WIDTH),
+                    (corner.1 + dy - vy * second).rem_euclid(HEIGHT),
+                )
+            } else {
+                (rng.gen_range(0..WIDTH), rng.gen_range(0..HEIGHT))
+            };
+
+            format!("p={px},{py} v={vx},{vy}")
+        })
+        .join("\n")
+}
+
+fn day15(rng: &mut StdRng, size: usize) -> String {
+    let size = size.max(3);
+
+    let mut grid = vec![vec!['#'; size]; size];
+    for row in &mut grid[1..size - 1] {
+        for tile in &mut row[1..size - 1] {
+            *tile = match rng.gen_range(0..10) {
+                0 => '#',
+                1 | 2 => 'O',
+                _ => '.',
+            };
+        }
+    }
+    grid[rng.gen_range(1..size - 1)][rng.gen_range(1..size - 1)] = '@';
+
+    let moves = (0..size * size * 8)
+        .map(|_| pick(rng, "^>v<"))
+        .chunks(1000)
+        .into_iter()
+        .map(Iterator::collect::<String>)
+        .join("\n");
+
+    format!("{}\n\n{moves}", render(&grid))
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Maze {
+    /// Corridors with dead ends and several routes between most tiles.
+    Loops,
+    /// A single track from start to end.
+    Track,
+}
+
+/// A maze of walls with `S` in the bottom left and `E` in the top right corner.
+fn maze(rng: &mut StdRng, size: usize, kind: Maze) -> String {
+    // cells sit on odd coordinates, with the walls between them on even ones
+    let size = (size.max(5) - 1) / 2 * 2 + 1;
+    let cells = size / 2;
+
+    let mut grid = vec![vec!['#'; size]; size];
+    let mut visited = vec![vec![false; cells]; cells];
+    let mut stack = vec![(cells - 1, 0)];
+    let mut track = vec![];
+    visited[cells - 1][0] = true;
+
+    while let Some(&(r, c)) = stack.last() {
+        grid[2 * r + 1][2 * c + 1] = '.';
+
+        let next = [(-1, 0), (0, 1), (1, 0), (0, -1)]
+            .into_iter()
+            .filter_map(|(dr, dc)| Some((r.checked_add_signed(dr)?, c.checked_add_signed(dc)?)))
+            .filter(|&(r, c)| r < cells && c < cells && !visited[r][c])
+            .collect_vec();
+
+        match next.choose(rng) {
+            Some(&(nr, nc)) => {
+                visited[nr][nc] = true;
+                grid[r + nr + 1][c + nc + 1] = '.';
+                stack.push((nr, nc));
+                if (nr, nc) == (0, cells - 1) {
+                    track.clone_from(&stack);
+                }
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    match kind {
+        Maze::Loops => {
+            for _ in 0..cells * cells / 10 {
+                let (r, c) = (rng.gen_range(1..size - 1), rng.gen_range(1..size - 1));
+                if (r + c) % 2 == 1 {
+                    grid[r][c] = '.';
+                }
+            }
+        }
+        // the stack held the way from start to end when the end was reached
+        Maze::Track => {
+            grid = vec![vec!['#'; size]; size];
+            for (&(r, c), &(nr, nc)) in track.iter().tuple_windows() {
+                grid[2 * r + 1][2 * c + 1] = '.';
+                grid[r + nr + 1][c + nc + 1] = '.';
+            }
+        }
+    }
+
+    grid[size - 2][1] = 'S';
+    grid[1][size - 2] = 'E';
+
+    render(&grid)
+}
+
+fn day16(rng: &mut StdRng, size: usize) -> String {
+    maze(rng, size, Maze::Loops)
+}
+
+fn day17(rng: &mut StdRng, size: usize) -> String {
+    let digits = size.min(20);
+    let reg_a = (0..digits).fold(u64::from(rng.gen_range(1..8u8)), |a, _| {
+        a * 8 + rng.gen_range(0..8)
+    });
+
+    // the shape of the real programs: each loop prints one value and drops
+    // the lowest octal digit of A, so the program always halts
+    let program = [
+        2,
+        4,
+        1,
+        rng.gen_range(0..8),
+        7,
+        5,
+        1,
+        rng.gen_range(0..8),
+        4,
+        rng.gen_range(0..8),
+        0,
+        3,
+        5,
+        5,
+        3,
+        0,
+    ];
+
+    format!(
+        "Register A: {reg_a}\nRegister B: 0\nRegister C: 0\n\nProgram: {}",
+        program.iter().join(",")
+    )
+}
+
+fn day18(rng: &mut StdRng, size: usize) -> String {
+    let size = size.max(2);
+
+    let mut bytes = (0..size)
+        .cartesian_product(0..size)
+        .filter(|&pos| pos != (0, 0) && pos != (size - 1, size - 1))
+        .collect_vec();
+    bytes.shuffle(rng);
+    bytes.truncate(bytes.len() * 2 / 3);
+
+    bytes
+        .iter()
+        .map(|(row, col)| format!("{col},{row}"))
+        .join("\n")
+}
+
+fn day19(rng: &mut StdRng, size: usize) -> String {
+    const COLORS: &str = "wubrg";
+
+    // without one single-stripe towel, not every design can be made
+    let missing = pick(rng, COLORS).to_string();
+    let mut towels = HashSet::new();
+    for _ in 0..size * 10 {
+        if towels.len() == size {
+            break;
+        }
+
+        let towel = (0..rng.gen_range(1..=8))
+            .map(|_| pick(rng, COLORS))
+            .collect::<String>();
+        if towel != missing {
+            towels.insert(towel);
+        }
+    }
+    let mut towels = towels.into_iter().sorted().collect_vec();
+    towels.shuffle(rng);
+
+    let designs = (0..size)
+        .map(|_| {
+            if rng.gen_bool(0.7) {
+                (0..rng.gen_range(3..=12))
+                    .filter_map(|_| towels.choose(rng))
+                    .join("")
+            } else {
+                (0..rng.gen_range(20..=60))
+                    .map(|_| pick(rng, COLORS))
+                    .collect()
+            }
+        })
+        .join("\n");
+
+    format!("{}\n\n{designs}", towels.join(", "))
+}
+
+fn day20(rng: &mut StdRng, size: usize) -> String {
+    maze(rng, size, Maze::Track)
+}
+
+fn day21(rng: &mut StdRng, size: usize) -> String {
+    (0..size)
+        .map(|_| format!("{:03}A", rng.gen_range(0..1000)))
+        .join("\n")
+}
+
+fn day22(rng: &mut StdRng, size: usize) -> String {
+    (0..size).map(|_| rng.gen_range(1..16_777_216)).join("\n")
+}
+
+fn day23(rng: &mut StdRng, size: usize) -> String {
+    const LETTERS: &str = "abcdefghijklmnopqrstuvwxyz";
+
+    let mut names = LETTERS
+        .chars()
+        .cartesian_product(LETTERS.chars())
+        .map(|(a, b)| format!("{a}{b}"))
+        .collect_vec();
+    names.shuffle(rng);
+    names.truncate(size.clamp(2, names.len()));
+
+    let mut edges = HashSet::new();
+    for (i, name) in names.iter().enumerate() {
+        for _ in 0..6 {
+            let other = rng.gen_range(0..names.len());
+            if other != i {
+                edges.insert((name.min(&names[other]), name.max(&names[other])));
+            }
+        }
+    }
+
+    // plant a LAN party larger than what random edges produce
+    let party = names
+        .choose_multiple(rng, names.len().min(13))
+        .collect_vec();
+    for (a, b) in party.into_iter().tuple_combinations() {
+        edges.insert((a.min(b), a.max(b)));
+    }
+
+    let mut edges = edges
+        .into_iter()
+        .sorted()
+        .map(|(a, b)| {
+            if rng.gen_bool(0.5) {
+                format!("{a}-{b}")
+            } else {
+                format!("{b}-{a}")
+            }
+        })
+        .collect_vec();
+    edges.shuffle(rng);
+
+    edges.join("\n")
+}
+
+struct Gate {
+    lhs: String,
+    kind: &'static str,
+    rhs: String,
+    output: String,
+}
+
+fn day24(rng: &mut StdRng, size: usize) -> String {
+    let bits = size.clamp(1, 99);
+
+    let mut names = HashSet::new();
+    let mut wire = || loop {
+        let name = (0..3)
+            .map(|_| pick(rng, "abcdefghijklmnopqrstuvw"))
+            .collect::<String>();
+        if names.insert(name.clone()) {
+            return name;
+        }
+    };
+
+    // a ripple-carry adder: z = x + y
+    let mut gates = vec![];
+    let mut gate = |lhs: &str, kind, rhs: &str, output: &str| {
+        gates.push(Gate {
+            lhs: lhs.to_string(),
+            kind,
+            rhs: rhs.to_string(),
+            output: output.to_string(),
+        });
+    };
+
+    let mut carry = if bits == 1 { "z01".to_string() } else { wire() };
+    gate("x00", "XOR", "y00", "z00");
+    gate("x00", "AND", "y00", &carry);
+
+    for bit in 1..bits {
+        let (x, y, z) = (
+            format!("x{bit:02}"),
+            format!("y{bit:02}"),
+            format!("z{bit:02}"),
+        );
+        let (sum, both, either) = (wire(), wire(), wire());
+        let next = if bit + 1 == bits {
+            format!("z{bits:02}")
+        } else {
+            wire()
+        };
+
+        gate(&x, "XOR", &y, &sum);
+        gate(&x, "AND", &y, &both);
+        gate(&sum, "XOR", &carry, &z);
+        gate(&sum, "AND", &carry, &either);
+        gate(&both, "OR", &either, &next);
+        carry = next;
+    }
+
+    // swap outputs within four bits like the real puzzle does: once the two
+    // gates reading x and y, otherwise the z gate with one of the carry gates
+    let mut swapped = (1..bits.saturating_sub(1)).collect_vec();
+    swapped.shuffle(rng);
+    for (i, &bit) in swapped.iter().take(4).enumerate() {
+        let sum = 2 + 5 * (bit - 1);
+        let (a, b) = if i == 0 {
+            (sum, sum + 1)
+        } else {
+            (sum + 2, sum + [1, 3, 4][rng.gen_range(0..3)])
+        };
+
+        let output = gates[a].output.clone();
+        gates[a].output = std::mem::replace(&mut gates[b].output, output);
+    }
+
+    let inputs = ['x', 'y']
+        .into_iter()
+        .cartesian_product(0..bits)
+        .map(|(name, bit)| format!("{name}{bit:02}: {}", rng.gen_range(0..2)))
+        .join("\n");
+
+    let mut gates = gates
+        .into_iter()
+        .map(
+            |Gate {
+                 lhs,
+                 kind,
+                 rhs,
+                 output,
+             }| {
+                let (lhs, rhs) = if rng.gen_bool(0.5) {
+                    (lhs, rhs)
+                } else {
+                    (rhs, lhs)
+                };
+                format!("{lhs} {kind} {rhs} -> {output}")
+            },
+        )
+        .collect_vec();
+    gates.shuffle(rng);
+
+    format!("{inputs}\n\n{}", gates.join("\n"))
+}
+
+fn day25(rng: &mut StdRng, size: usize) -> String {
+    (0..size)
+        .map(|_| {
+            let is_lock = rng.gen_bool(0.5);
+            let heights = (0..5).map(|_| rng.gen_range(0..=5)).collect_vec();
+
+            (0..7)
+                .map(|row| {
+                    heights
+                        .iter()
+                        .map(|&height| {
+                            let filled = if is_lock {
+                                row <= height
+                            } else {
+                                6 - row <= height
+                            };
+                            if filled {
+                                '#'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect::<String>()
+                })
+                .join("\n")
+        })
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Part;
+
+    #[test]
+    fn test_parses() {
+        for solver in solver::solvers() {
+            let day = solver.day();
+            for seed in 0..3 {
+                let input = generate(day, 10, seed).expect("registered day");
+                for part in Part::ALL {
+                    if let Err(e) = solver.parse(part, &input) {
+                        panic!("day {day} seed {seed}: {e}\n{input}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_seeded() {
+        let input = generate(16, 21, 7);
+        assert_eq!(input, generate(16, 21, 7));
+        assert_ne!(input, generate(16, 21, 8));
+        assert!(input.is_ok_and(|maze| maze.lines().count() == 21));
+
+        assert_eq!(generate(26, 10, 0), Err(Error::UnknownDay(26)));
+        assert!(generate(1, 0, 0).is_err());
+    }
+}
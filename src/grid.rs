@@ -0,0 +1,205 @@
+use crate::error::ParseError;
+use crate::parse::Source;
+use crate::pos::{Dir, IPos, Pos};
+use pathfinding::matrix::Matrix;
+
+pub type Grid<T> = Matrix<T>;
+
+/// Tiles of the mazes in days 16 and 20.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MazeTile {
+    Wall,
+    Open,
+    Start,
+    End,
+}
+
+impl TryFrom<char> for MazeTile {
+    type Error = char;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '#' => Ok(Self::Wall),
+            '.' => Ok(Self::Open),
+            'S' => Ok(Self::Start),
+            'E' => Ok(Self::End),
+            c => Err(c),
+        }
+    }
+}
+
+/// Parses `text` into a grid of tiles.
+pub fn parse<T: TryFrom<char>>(src: &Source<'_>, text: &str) -> Result<Grid<T>, ParseError> {
+    parse_with(src, text, |c| T::try_from(c).ok())
+}
+
+/// Parses `text` into a rectangular grid, mapping each character with `tile`.
+pub fn parse_with<T>(
+    src: &Source<'_>,
+    text: &str,
+    tile: impl Fn(char) -> Option<T>,
+) -> Result<Grid<T>, ParseError> {
+    let mut columns = None;
+    let mut values = vec![];
+    let mut rows = 0;
+
+    for line in text.lines() {
+        let width = line.chars().count();
+        if width == 0 {
+            return Err(src.error(line, "unexpected empty grid row"));
+        }
+
+        if *columns.get_or_insert(width) != width {
+            return Err(src.error(line, "expected all grid rows to have the same length"));
+        }
+
+        for (idx, c) in line.char_indices() {
+            let value = tile(c)
+                .ok_or_else(|| src.error(&line[idx..idx + c.len_utf8()], "unexpected tile"))?;
+            values.push(value);
+        }
+
+        rows += 1;
+    }
+
+    let columns = columns.ok_or_else(|| src.error(text, "expected a non-empty grid"))?;
+    Matrix::from_vec(rows, columns, values)
+        .map_err(|_| src.error(text, "expected a rectangular grid"))
+}
+
+pub trait GridExt<T> {
+    /// All positions, in row-major order.
+    fn positions(&self) -> impl Iterator<Item = Pos> + '_;
+
+    /// The first position holding `tile`, in row-major order.
+    fn find(&self, tile: &T) -> Option<Pos>;
+
+    /// All positions holding `tile`, in row-major order.
+    fn find_all<'a>(&'a self, tile: &'a T) -> impl Iterator<Item = Pos> + 'a;
+
+    /// The position `delta` away from `pos`, if it is inside the grid.
+    fn offset(&self, pos: Pos, delta: IPos) -> Option<Pos>;
+
+    /// In-bounds orthogonal neighbors of `pos`, in [`Dir::ALL`] order.
+    fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_;
+
+    /// In-bounds orthogonal and diagonal neighbors of `pos`, in
+    /// [`IPos::ADJACENT`] order.
+    fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_;
+
+    /// Positions visited by repeatedly stepping along `delta` from `start`,
+    /// excluding `start` and stopping at the edge of the grid.
+    fn ray(&self, start: Pos, delta: IPos) -> impl Iterator<Item = Pos> + '_;
+}
+
+impl<T: PartialEq> GridExt<T> for Grid<T> {
+    fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        self.keys().map(Pos::from)
+    }
+
+    fn find(&self, tile: &T) -> Option<Pos> {
+        self.positions().find(|&pos| self[pos] == *tile)
+    }
+
+    fn find_all<'a>(&'a self, tile: &'a T) -> impl Iterator<Item = Pos> + 'a {
+        self.positions().filter(move |&pos| self[pos] == *tile)
+    }
+
+    fn offset(&self, pos: Pos, delta: IPos) -> Option<Pos> {
+        pos.offset(delta)
+            .filter(|next| next.row < self.rows && next.col < self.columns)
+    }
+
+    fn neighbors4(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Dir::ALL
+            .into_iter()
+            .filter_map(move |dir| pos.step_in(dir, self))
+    }
+
+    fn neighbors8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        IPos::ADJACENT
+            .into_iter()
+            .filter_map(move |delta| self.offset(pos, delta))
+    }
+
+    fn ray(&self, start: Pos, delta: IPos) -> impl Iterator<Item = Pos> + '_ {
+        let first = self
+            .offset(start, delta)
+            .filter(|_| delta != IPos::default());
+        std::iter::successors(first, move |&pos| self.offset(pos, delta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    const TEST_INPUT: &str = "#S.\n\
+                              #.#\n\
+                              ..E";
+
+    fn maze() -> Grid<MazeTile> {
+        parse(&Source::new(16, TEST_INPUT), TEST_INPUT).expect("valid grid")
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = maze();
+        assert_eq!((grid.rows, grid.columns), (3, 3));
+        assert_eq!(grid[(2, 2)], MazeTile::End);
+
+        let grid = parse_with(&Source::new(10, "12\n34"), "12\n34", |c| c.to_digit(10));
+        assert_eq!(grid.map(|g| g[(1, 0)]), Ok(3));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = "ab\nc";
+        let err = parse::<char>(&Source::new(4, input), input);
+        assert_eq!(err.map_err(|e| (e.line, e.column)), Err((2, 1)));
+
+        let input = "#.\n#x";
+        let err = parse::<MazeTile>(&Source::new(16, input), input);
+        assert_eq!(err.map_err(|e| (e.line, e.column)), Err((2, 2)));
+
+        assert!(parse::<char>(&Source::new(4, ""), "").is_err());
+    }
+
+    #[test]
+    fn test_find() {
+        let grid = maze();
+        assert_eq!(grid.find(&MazeTile::Start), Some(Pos::new(0, 1)));
+        assert_eq!(
+            grid.find_all(&MazeTile::Wall).collect_vec(),
+            [(0, 0), (1, 0), (1, 2)].map(Pos::from)
+        );
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = maze();
+        assert_eq!(
+            grid.neighbors4(Pos::new(0, 0)).collect_vec(),
+            [(0, 1), (1, 0)].map(Pos::from)
+        );
+        assert_eq!(grid.neighbors4(Pos::new(1, 1)).count(), 4);
+        assert_eq!(
+            grid.neighbors8(Pos::new(2, 2)).collect_vec(),
+            [(1, 2), (2, 1), (1, 1)].map(Pos::from)
+        );
+        assert_eq!(grid.neighbors8(Pos::new(1, 1)).count(), 8);
+    }
+
+    #[test]
+    fn test_ray() {
+        let grid = maze();
+        let ray = |start, delta| grid.ray(Pos::from(start), delta).collect_vec();
+        assert_eq!(
+            ray((0, 0), IPos::new(1, 1)),
+            [(1, 1), (2, 2)].map(Pos::from)
+        );
+        assert!(ray((2, 0), Dir::West.delta()).is_empty());
+        assert!(ray((1, 1), IPos::default()).is_empty());
+    }
+}
@@ -0,0 +1,778 @@
+use aoc_2024::answers::{AnswerStore, Status};
+use aoc_2024::bench::{self, Baseline};
+use aoc_2024::budget::{CancelToken, Limits};
+use aoc_2024::error::Error;
+use aoc_2024::gen;
+use aoc_2024::params::PuzzleParams;
+use aoc_2024::runner::{self, DayJob};
+use aoc_2024::solver::{self, Part};
+use aoc_2024::viz::{AsciiSink, ImageFormat, ImageSink, Palette, Sample};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+#[derive(Parser)]
+#[command(name = "aoc2024", about = "Advent of Code 2024 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve puzzles and print the answers
+    Run(RunArgs),
+
+    /// Time the generator and solver of each day and compare against a baseline
+    Bench {
+        /// Only benchmark this day
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: Option<u8>,
+
+        /// Only benchmark this part
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// Directory holding the `day<N>.txt` inputs [default: a generated
+        /// input of real size for each day, the one the committed baseline
+        /// was measured on]
+        #[arg(long)]
+        inputs: Option<PathBuf>,
+
+        /// Input file of the benchmarked day, or `-` for stdin
+        #[arg(long, requires = "day", conflicts_with = "inputs")]
+        input: Option<String>,
+
+        /// Number of timed runs; the median is reported
+        #[arg(long, default_value_t = 10)]
+        iterations: u32,
+
+        /// Baseline file to compare against; a missing one is an error
+        #[arg(long, default_value = "benches/baseline.json")]
+        baseline: PathBuf,
+
+        /// Write the results to the baseline instead of comparing
+        #[arg(long)]
+        save: bool,
+
+        /// Slowdown in percent above which a part is flagged
+        #[arg(long, default_value_t = 20)]
+        threshold: u32,
+    },
+
+    /// Solve a simulation puzzle while rendering its frames
+    Viz {
+        #[command(flatten)]
+        target: Target,
+
+        /// Write numbered images to this directory instead of printing text
+        #[arg(long)]
+        out: Option<PathBuf>,
+
+        /// Image format of the written frames
+        #[arg(long, value_enum, default_value_t = Format::Ppm)]
+        format: Format,
+
+        /// Pixels per tile in written images
+        #[arg(long, default_value_t = 4)]
+        scale: usize,
+
+        /// Only keep every N-th frame
+        #[arg(long, default_value_t = 1)]
+        every: usize,
+    },
+
+    /// Print the graph of a graph-shaped puzzle in Graphviz DOT format
+    Graph {
+        #[command(flatten)]
+        target: Target,
+
+        /// Write `part<N>.dot` files to this directory instead of printing
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Solve a puzzle with each implementation of its parts and compare them
+    Variants {
+        #[command(flatten)]
+        target: Target,
+    },
+
+    /// Check that an input has the shape the solution of a puzzle relies on
+    Validate {
+        #[command(flatten)]
+        target: Target,
+    },
+
+    /// Print a random input for a day
+    Gen {
+        /// Day to generate an input for (1-25)
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: u8,
+
+        /// Size of the input, whose unit depends on the day [default: that of a real input]
+        #[arg(long)]
+        size: Option<usize>,
+
+        /// Seed of the random generator
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+
+        /// Write the input to this file instead of stdout
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Check every stored input against the answers file, or one part of an
+    /// input against `--expected`
+    Verify {
+        /// Answers file; input paths in it are relative to its directory
+        #[arg(long, default_value = "input/2024/answers.toml")]
+        answers: PathBuf,
+
+        /// Only verify this day
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=25))]
+        day: Option<u8>,
+
+        /// Only verify this part
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// Input file, or `-` for stdin [default: input/2024/day<N>.txt]
+        #[arg(long, requires = "expected")]
+        input: Option<String>,
+
+        /// Check `--part` of the input of `--day` against this answer instead of
+        /// the answers file
+        #[arg(long, requires_all = ["day", "part"], conflicts_with = "answers")]
+        expected: Option<String>,
+
+        /// Give up on a part after this many seconds, where the solver iterates
+        #[arg(long, value_name = "SECONDS", value_parser = parse_timeout, default_value = "60")]
+        timeout: Duration,
+    },
+}
+
+/// Image formats; PNG is only offered when built with the `png` feature.
+#[derive(Copy, Clone, ValueEnum)]
+enum Format {
+    Ppm,
+    #[cfg(feature = "png")]
+    Png,
+}
+
+impl From<Format> for ImageFormat {
+    fn from(value: Format) -> Self {
+        match value {
+            Format::Ppm => Self::Ppm,
+            #[cfg(feature = "png")]
+            Format::Png => Self::Png,
+        }
+    }
+}
+
+#[derive(Args)]
+#[allow(clippy::struct_excessive_bools)]
+struct RunArgs {
+    /// Day to solve; every day if omitted
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: Option<u8>,
+
+    /// Part to solve; both parts if omitted
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Input file, or `-` for stdin [default: input/2024/day<N>.txt]
+    #[arg(long, requires = "day")]
+    input: Option<String>,
+
+    /// Override a puzzle constant, e.g. `--param width=11`
+    #[arg(long = "param", value_name = "KEY=VALUE", value_parser = parse_param, requires = "day")]
+    params: Vec<(String, String)>,
+
+    /// Print the results as a JSON document
+    #[arg(long)]
+    json: bool,
+
+    /// Print a table of answers and timings per day, followed by a summary
+    #[arg(long, conflicts_with = "json")]
+    table: bool,
+
+    /// Run the days concurrently, one thread each
+    #[arg(long)]
+    concurrent: bool,
+
+    /// Give up on a part after this many seconds, where the solver iterates
+    #[arg(long, value_name = "SECONDS", value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+
+    /// Give up on a part after this many iterations, where the solver iterates
+    #[arg(long, value_name = "STEPS")]
+    max_steps: Option<u64>,
+
+    /// Report the nodes expanded, successor calls, peak frontier and time of
+    /// each part's searches
+    #[arg(long)]
+    search_stats: bool,
+}
+
+#[derive(Args)]
+struct Target {
+    /// Day to solve (1-25)
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=25))]
+    day: u8,
+
+    /// Part to solve; both parts if omitted
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    part: Option<u8>,
+
+    /// Input file, or `-` for stdin [default: input/2024/day<N>.txt]
+    #[arg(long)]
+    input: Option<String>,
+
+    /// Override a puzzle constant, e.g. `--param width=11`
+    #[arg(long = "param", value_name = "KEY=VALUE", value_parser = parse_param)]
+    params: Vec<(String, String)>,
+}
+
+fn parse_timeout(s: &str) -> Result<Duration, String> {
+    s.parse()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| format!("expected a number of seconds, got `{s}`"))
+}
+
+fn parse_param(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got `{s}`"))
+}
+
+impl Target {
+    fn parts(&self) -> Vec<Part> {
+        self.part
+            .and_then(|part| Part::try_from(part).ok())
+            .map_or_else(|| Part::ALL.to_vec(), |part| vec![part])
+    }
+
+    fn params(&self) -> PuzzleParams {
+        let mut params = PuzzleParams::new();
+        for (key, value) in &self.params {
+            params.set(key, value);
+        }
+        params
+    }
+
+    fn read_input(&self) -> Result<String, Error> {
+        match self.input.as_deref() {
+            Some("-") => {
+                let mut buf = String::new();
+                std::io::stdin()
+                    .read_to_string(&mut buf)
+                    .map_err(|e| Error::io(Path::new("<stdin>"), &e))?;
+                Ok(buf)
+            }
+            input => {
+                let path = input.map_or_else(
+                    || PathBuf::from(format!("input/2024/day{}.txt", self.day)),
+                    PathBuf::from,
+                );
+                std::fs::read_to_string(&path).map_err(|e| Error::io(&path, &e))
+            }
+        }
+    }
+}
+
+fn run(args: &RunArgs) -> Result<(), String> {
+    let days = args.day.map_or_else(
+        || {
+            solver::solvers()
+                .iter()
+                .map(|solver| solver.day())
+                .collect()
+        },
+        |day| vec![day],
+    );
+
+    let mut jobs = vec![];
+    for day in days {
+        let target = Target {
+            day,
+            part: args.part,
+            input: args.input.clone(),
+            params: args.params.clone(),
+        };
+        jobs.push(DayJob {
+            solver: solver::solver(day).map_err(|e| e.to_string())?,
+            input: target.read_input(),
+            params: target.params(),
+            parts: target.parts(),
+            limits: Limits {
+                timeout: args.timeout,
+                max_steps: args.max_steps,
+            },
+            search_stats: args.search_stats,
+        });
+    }
+
+    let token = interruptible()?;
+
+    let start = Instant::now();
+    let runs = runner::run_all(&jobs, args.concurrent, &token);
+    let wall_time = start.elapsed();
+
+    if args.json {
+        println!("{}", runner::to_json(&runs));
+    } else if args.table {
+        print!("{}", runner::report(&runs, wall_time));
+    } else {
+        for run in &runs {
+            let (day, part) = (run.day, run.part);
+            match &run.result {
+                Ok(answer) => println!(
+                    "day {day:02} part {part}: {answer} ({:.2?})",
+                    run.total_time()
+                ),
+                Err(e) => println!("day {day:02} part {part}: FAILED, {e}"),
+            }
+            if let Some(stats) = run.search.filter(|stats| stats.searches > 0) {
+                println!("  searches: {stats}");
+            }
+            if let Some(size) = run.cache_size {
+                println!("  cache: {size} entries");
+            }
+        }
+    }
+
+    match runs.iter().filter(|run| run.result.is_err()).count() {
+        0 => Ok(()),
+        failed => Err(format!("{failed} parts failed")),
+    }
+}
+
+struct BenchOptions<'a> {
+    day: Option<u8>,
+    part: Option<u8>,
+    inputs: Option<&'a Path>,
+    input: Option<&'a str>,
+    iterations: u32,
+    baseline: &'a Path,
+    save: bool,
+    threshold: u32,
+}
+
+fn bench(opts: &BenchOptions<'_>) -> Result<(), String> {
+    let mut timings = vec![];
+
+    for solver in solver::solvers() {
+        let day = solver.day();
+        if opts.day.is_some_and(|d| d != day) {
+            continue;
+        }
+
+        let input = if let Some(input) = opts.input {
+            let target = Target {
+                day,
+                part: opts.part,
+                input: Some(input.to_string()),
+                params: vec![],
+            };
+            target.read_input().map_err(|e| e.to_string())?
+        } else if let Some(inputs) = opts.inputs {
+            let path = inputs.join(format!("day{day}.txt"));
+            let Ok(input) = std::fs::read_to_string(&path) else {
+                println!("day {day:02}: skipped, no input at {}", path.display());
+                continue;
+            };
+            input
+        } else {
+            gen::generate(day, gen::default_size(day), 0).map_err(|e| e.to_string())?
+        };
+
+        for part in Part::ALL {
+            if opts.part.is_some_and(|p| p != part.number()) {
+                continue;
+            }
+
+            let timing =
+                bench::measure(*solver, part, &input, &PuzzleParams::new(), opts.iterations)
+                    .map_err(|e| e.to_string())?;
+            println!(
+                "day {day:02} part {part}: parse {:.2?}, solve {:.2?}",
+                timing.parse_time(),
+                timing.solve_time()
+            );
+            timings.push(timing);
+        }
+    }
+
+    if opts.save {
+        let mut baseline = if opts.baseline.exists() {
+            Baseline::load(opts.baseline).map_err(|e| e.to_string())?
+        } else {
+            Baseline::default()
+        };
+        for timing in timings {
+            baseline.update(timing);
+        }
+
+        baseline.save(opts.baseline).map_err(|e| e.to_string())?;
+        println!("saved baseline to {}", opts.baseline.display());
+        return Ok(());
+    }
+
+    if !opts.baseline.exists() {
+        return Err(format!(
+            "no baseline at {}, create it with --save",
+            opts.baseline.display()
+        ));
+    }
+
+    let baseline = Baseline::load(opts.baseline).map_err(|e| e.to_string())?;
+    let regressions = bench::compare(&baseline, &timings, opts.threshold);
+    for r in &regressions {
+        println!(
+            "day {:02} part {} {}: {:.2?} -> {:.2?} (+{}%)",
+            r.day,
+            r.part,
+            r.stage,
+            r.baseline,
+            r.current,
+            r.percent()
+        );
+    }
+
+    if regressions.is_empty() {
+        println!("no regressions above {}%", opts.threshold);
+        Ok(())
+    } else {
+        Err(format!(
+            "{} regressions above {}%",
+            regressions.len(),
+            opts.threshold
+        ))
+    }
+}
+
+/// Checks the single part of `target` against `expected`.
+fn check(target: &Target, expected: &str, timeout: Duration) -> Result<(), String> {
+    let solver = solver::solver(target.day).map_err(|e| e.to_string())?;
+    let input = target.read_input().map_err(|e| e.to_string())?;
+    let [part] = target.parts()[..] else {
+        return Err("--expected needs a single --part".to_string());
+    };
+
+    let budget = verify_limits(timeout).start(interruptible()?);
+    let mut cache = solver.new_cache();
+    let run = runner::run_part(
+        solver,
+        part,
+        &input,
+        &target.params(),
+        &budget,
+        cache.as_deref_mut(),
+    );
+    let answer = run.result.map_err(|e| e.to_string())?;
+    if answer.to_string() == expected {
+        println!("day {:02} part {part}: ok", target.day);
+        Ok(())
+    } else {
+        Err(format!(
+            "day {:02} part {part}: expected {expected}, got {answer}",
+            target.day
+        ))
+    }
+}
+
+struct VizOptions<'a> {
+    out: Option<&'a Path>,
+    format: Format,
+    scale: usize,
+    every: usize,
+}
+
+fn viz(target: &Target, opts: &VizOptions<'_>) -> Result<(), String> {
+    let input = target.read_input().map_err(|e| e.to_string())?;
+    let params = target.params();
+    let solver = solver::solver(target.day).map_err(|e| e.to_string())?;
+
+    for part in target.parts() {
+        let parsed = solver.parse(part, &input).map_err(|e| e.to_string())?;
+
+        let answer = if let Some(out) = opts.out {
+            let dir = out.join(format!("part{part}"));
+            let mut sink = ImageSink::new(&dir, opts.format.into(), Palette::default(), opts.scale);
+            let answer = solver
+                .visualize(
+                    part,
+                    &parsed,
+                    &params,
+                    &mut Sample::new(&mut sink, opts.every),
+                )
+                .map_err(|e| e.to_string())?;
+            let written = sink.finish().map_err(|e| e.to_string())?;
+            println!("wrote {written} frames to {}", dir.display());
+            answer
+        } else {
+            let mut sink = AsciiSink::new(std::io::stdout().lock());
+            let answer = solver
+                .visualize(
+                    part,
+                    &parsed,
+                    &params,
+                    &mut Sample::new(&mut sink, opts.every),
+                )
+                .map_err(|e| e.to_string())?;
+            sink.finish().map_err(|e| e.to_string())?;
+            answer
+        };
+
+        println!("day {:02} part {part}: {answer}", target.day);
+    }
+
+    Ok(())
+}
+
+fn graph(target: &Target, out: Option<&Path>) -> Result<(), String> {
+    let input = target.read_input().map_err(|e| e.to_string())?;
+    let params = target.params();
+    let solver = solver::solver(target.day).map_err(|e| e.to_string())?;
+
+    for part in target.parts() {
+        let parsed = solver.parse(part, &input).map_err(|e| e.to_string())?;
+        let dot = solver
+            .graph(part, &parsed, &params)
+            .map_err(|e| e.to_string())?
+            .to_string();
+
+        if let Some(out) = out {
+            std::fs::create_dir_all(out).map_err(|e| Error::io(out, &e).to_string())?;
+            let path = out.join(format!("part{part}.dot"));
+            std::fs::write(&path, dot).map_err(|e| Error::io(&path, &e).to_string())?;
+            println!("wrote {}", path.display());
+        } else {
+            print!("{dot}");
+        }
+    }
+
+    Ok(())
+}
+
+fn variants(target: &Target) -> Result<(), String> {
+    let input = target.read_input().map_err(|e| e.to_string())?;
+    let params = target.params();
+    let solver = solver::solver(target.day).map_err(|e| e.to_string())?;
+
+    let mut disagreeing = 0;
+    for part in target.parts() {
+        let runs =
+            runner::run_variants(solver, part, &input, &params).map_err(|e| e.to_string())?;
+        disagreeing += runner::disagreeing(&runs).len();
+
+        println!("day {:02} part {part}:", target.day);
+        print!("{}", runner::variant_report(&runs));
+    }
+
+    match disagreeing {
+        0 => Ok(()),
+        n => Err(format!("{n} variants disagree")),
+    }
+}
+
+fn validate(target: &Target) -> Result<(), String> {
+    let input = target.read_input().map_err(|e| e.to_string())?;
+
+    let mut failed = 0;
+    for part in target.parts() {
+        let label = format!("day {:02} part {part}", target.day);
+        match solver::validate(target.day, part, &input) {
+            Ok(violations) if violations.is_empty() => println!("{label}: ok"),
+            Ok(violations) => {
+                for violation in &violations {
+                    println!("{label}: {violation}");
+                }
+                failed += violations.len();
+            }
+            Err(e) => {
+                println!("{label}: {e}");
+                failed += 1;
+            }
+        }
+    }
+
+    match failed {
+        0 => Ok(()),
+        n => Err(format!("{n} problems with the input")),
+    }
+}
+
+fn generate(day: u8, size: Option<usize>, seed: u64, out: Option<&Path>) -> Result<(), String> {
+    let size = size.unwrap_or_else(|| gen::default_size(day));
+    let input = gen::generate(day, size, seed).map_err(|e| e.to_string())?;
+
+    if let Some(path) = out {
+        return std::fs::write(path, input).map_err(|e| Error::io(path, &e).to_string());
+    }
+
+    println!("{input}");
+    Ok(())
+}
+
+const fn verify_limits(timeout: Duration) -> Limits {
+    Limits {
+        timeout: Some(timeout),
+        max_steps: None,
+    }
+}
+
+/// A token that Ctrl-C cancels.
+fn interruptible() -> Result<CancelToken, String> {
+    let token = CancelToken::new();
+    token
+        .cancel_on_interrupt()
+        .map_err(|e| format!("cannot handle Ctrl-C: {e}"))?;
+    Ok(token)
+}
+
+fn verify(
+    answers: &Path,
+    day: Option<u8>,
+    part: Option<u8>,
+    timeout: Duration,
+) -> Result<(), String> {
+    let store = AnswerStore::load(answers).map_err(|e| e.to_string())?;
+    let checks = store
+        .verify(day, &verify_limits(timeout), &interruptible()?)
+        .into_iter()
+        .filter(|check| part.is_none_or(|part| part == check.part.number()));
+
+    let (mut passed, mut failed, mut missing, mut no_input) = (0, 0, 0, 0);
+    for check in checks {
+        let label = match &check.id {
+            Some(id) => format!("day {:02} part {} [{id}]", check.day, check.part),
+            None => format!("day {:02} part {}", check.day, check.part),
+        };
+        let answer = check
+            .answer
+            .as_ref()
+            .map_or_else(String::new, ToString::to_string);
+
+        match &check.status {
+            Status::Pass => {
+                passed += 1;
+                println!("{label}: ok ({:.2?})", check.elapsed);
+            }
+            Status::Fail { expected } => {
+                failed += 1;
+                println!(
+                    "{label}: FAILED, expected {expected}, got {answer} ({:.2?})",
+                    check.elapsed
+                );
+            }
+            Status::Missing => {
+                missing += 1;
+                println!(
+                    "{label}: no stored answer, got {answer} ({:.2?})",
+                    check.elapsed
+                );
+            }
+            Status::NoInput => {
+                no_input += 1;
+                println!("{label}: no stored input");
+            }
+            Status::Error(e) => {
+                failed += 1;
+                println!("{label}: FAILED, {e}");
+            }
+        }
+    }
+
+    println!("{passed} ok, {failed} failed, {missing} without answer, {no_input} without input");
+
+    if failed > 0 {
+        Err("verification failed".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match &cli.command {
+        Command::Run(args) => run(args),
+        Command::Bench {
+            day,
+            part,
+            inputs,
+            input,
+            iterations,
+            baseline,
+            save,
+            threshold,
+        } => bench(&BenchOptions {
+            day: *day,
+            part: *part,
+            inputs: inputs.as_deref(),
+            input: input.as_deref(),
+            iterations: *iterations,
+            baseline,
+            save: *save,
+            threshold: *threshold,
+        }),
+        Command::Viz {
+            target,
+            out,
+            format,
+            scale,
+            every,
+        } => viz(
+            target,
+            &VizOptions {
+                out: out.as_deref(),
+                format: *format,
+                scale: *scale,
+                every: *every,
+            },
+        ),
+        Command::Graph { target, out } => graph(target, out.as_deref()),
+        Command::Variants { target } => variants(target),
+        Command::Validate { target } => validate(target),
+        Command::Gen {
+            day,
+            size,
+            seed,
+            out,
+        } => generate(*day, *size, *seed, out.as_deref()),
+        Command::Verify {
+            answers,
+            day,
+            part,
+            input,
+            expected,
+            timeout,
+        } => match (day, part, expected) {
+            (Some(day), Some(part), Some(expected)) => check(
+                &Target {
+                    day: *day,
+                    part: Some(*part),
+                    input: input.clone(),
+                    params: vec![],
+                },
+                expected,
+                *timeout,
+            ),
+            _ => verify(answers, *day, *part, *timeout),
+        },
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
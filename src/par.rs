@@ -0,0 +1,105 @@
+//! Loops over independent items, spread over all cores when built with the
+//! `parallel` feature and run in order otherwise.
+//!
+//! Both builds share the same call sites and bounds, so closures must be
+//! `Sync` even without the feature. Results never depend on scheduling:
+//! `map` keeps the input order and sums are only taken over integers.
+//!
+//! Workers record their searches into the caller's [`scope`](crate::search::scope).
+
+#[cfg(feature = "parallel")]
+use crate::search;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::iter::Sum;
+
+#[cfg(feature = "parallel")]
+pub fn sum<I, T>(items: I, f: impl Fn(I::Item) -> T + Sync + Send) -> T
+where
+    I: IntoParallelIterator,
+    T: Sum + Send,
+{
+    let scope = search::scope();
+    items
+        .into_par_iter()
+        .map(|item| search::enter(scope.as_ref(), || f(item)))
+        .sum()
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn sum<I, T>(items: I, f: impl Fn(I::Item) -> T + Sync + Send) -> T
+where
+    I: IntoIterator,
+    T: Sum,
+{
+    items.into_iter().map(f).sum()
+}
+
+#[cfg(feature = "parallel")]
+pub fn count<I>(items: I, pred: impl Fn(&I::Item) -> bool + Sync + Send) -> usize
+where
+    I: IntoParallelIterator,
+{
+    let scope = search::scope();
+    items
+        .into_par_iter()
+        .filter(|item| search::enter(scope.as_ref(), || pred(item)))
+        .count()
+}
+
+#[cfg(not(feature = "parallel"))]
+pub fn count<I>(items: I, pred: impl Fn(&I::Item) -> bool + Sync + Send) -> usize
+where
+    I: IntoIterator,
+{
+    items.into_iter().filter(pred).count()
+}
+
+/// Applies `f` to every item, keeping their order.
+#[cfg(feature = "parallel")]
+pub fn map<I, T>(items: I, f: impl Fn(I::Item) -> T + Sync + Send) -> Vec<T>
+where
+    I: IntoParallelIterator,
+    T: Send,
+{
+    let scope = search::scope();
+    items
+        .into_par_iter()
+        .map(|item| search::enter(scope.as_ref(), || f(item)))
+        .collect()
+}
+
+/// Applies `f` to every item, keeping their order.
+#[cfg(not(feature = "parallel"))]
+pub fn map<I, T>(items: I, f: impl Fn(I::Item) -> T + Sync + Send) -> Vec<T>
+where
+    I: IntoIterator,
+{
+    items.into_iter().map(f).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordered() {
+        let squares = map(0..100u64, |n| n * n);
+        assert!(squares.iter().copied().eq((0..100).map(|n| n * n)));
+        assert_eq!(sum(&squares, |&n| n), 328_350);
+        assert_eq!(count(0..100, |n| n % 3 == 0), 34);
+    }
+
+    #[test]
+    fn test_search_scope() {
+        let (ends, stats) = crate::search::record(|| {
+            map(0..8u32, |n| {
+                crate::search::dijkstra(&n, |&m| [(m + 1, 1)], |&m| m == n + 2)
+                    .map(|(path, _)| path[2])
+            })
+        });
+
+        assert!(ends.into_iter().eq((2..10).map(Some)));
+        assert_eq!(stats.searches, 8);
+    }
+}
@@ -0,0 +1,144 @@
+use crate::error::Error;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Named overrides for the constants a puzzle bakes in, such as grid sizes or
+/// iteration counts.
+///
+/// Solvers read each value with a default, so an empty set reproduces the real
+/// puzzle and the examples only override what differs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PuzzleParams(BTreeMap<String, String>);
+
+impl PuzzleParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with(mut self, key: &str, value: impl ToString) -> Self {
+        self.set(key, value);
+        self
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
+    pub fn set(&mut self, key: &str, value: impl ToString) {
+        self.0.insert(key.to_string(), value.to_string());
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    pub fn get<T: FromStr>(&self, key: &str, default: T) -> Result<T, Error> {
+        self.0.get(key).map_or(Ok(default), |value| {
+            value.parse().map_err(|_| Error::InvalidParam {
+                key: key.to_string(),
+                value: value.clone(),
+            })
+        })
+    }
+
+    /// Like [`get`](Self::get), but rejects values that are zero or negative.
+    pub fn get_positive<T: FromStr + PartialOrd + Default + Display>(
+        &self,
+        key: &str,
+        default: T,
+    ) -> Result<T, Error> {
+        let value = self.get(key, default)?;
+        if value > T::default() {
+            Ok(value)
+        } else {
+            Err(Error::InvalidParam {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+        }
+    }
+
+    /// Like [`get`](Self::get), but rejects values outside `range`, for params
+    /// that set a recursion depth or the size of an allocation.
+    pub fn get_in<T: FromStr + PartialOrd + Display>(
+        &self,
+        key: &str,
+        default: T,
+        range: RangeInclusive<T>,
+    ) -> Result<T, Error> {
+        let value = self.get(key, default)?;
+        if range.contains(&value) {
+            Ok(value)
+        } else {
+            Err(Error::InvalidParam {
+                key: key.to_string(),
+                value: value.to_string(),
+            })
+        }
+    }
+}
+
+impl FromStr for PuzzleParams {
+    type Err = Error;
+
+    /// Parses a comma-separated list of `key=value` pairs.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut params = Self::new();
+
+        for pair in s.split(',').map(str::trim).filter(|pair| !pair.is_empty()) {
+            let (key, value) = pair.split_once('=').ok_or_else(|| Error::InvalidParam {
+                key: pair.to_string(),
+                value: String::new(),
+            })?;
+            params.set(key.trim(), value.trim());
+        }
+
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let params = PuzzleParams::new().with("width", 11).with("height", "x");
+
+        assert_eq!(params.get("width", 101), Ok(11));
+        assert_eq!(params.get("seconds", 100), Ok(100));
+        assert_eq!(
+            params.get("height", 103),
+            Err(Error::InvalidParam {
+                key: "height".to_string(),
+                value: "x".to_string()
+            })
+        );
+        assert!(params.get_positive("width", 101).is_ok());
+        assert!(PuzzleParams::new()
+            .with("width", 0)
+            .get_positive("width", 101)
+            .is_err());
+        assert_eq!(
+            PuzzleParams::new().get_positive("width", 0),
+            Err(Error::InvalidParam {
+                key: "width".to_string(),
+                value: "0".to_string()
+            })
+        );
+
+        let params = PuzzleParams::new().with("blinks", 1001);
+        assert_eq!(params.get_in("robots", 2, 0..=40), Ok(2));
+        assert!(params.get_in("blinks", 25, 0..=1000).is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        let params: PuzzleParams = "width=11, height=7".parse().expect("valid params");
+        assert_eq!(
+            params,
+            PuzzleParams::new().with("width", 11).with("height", 7)
+        );
+        assert!("width".parse::<PuzzleParams>().is_err());
+    }
+}
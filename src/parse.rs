@@ -0,0 +1,185 @@
+use crate::error::{Error, ParseError};
+use itertools::Itertools;
+use std::io::BufRead;
+use std::path::Path;
+use std::str::FromStr;
+
+const BOM: &str = "\u{feff}";
+
+/// Undoes what editors and downloads do to a puzzle input: strips a UTF-8
+/// BOM, turns CRLF into LF and drops trailing whitespace, both at the end of
+/// each line and at the end of the input.
+pub fn normalize(input: &str) -> String {
+    let input = input.strip_prefix(BOM).unwrap_or(input);
+    let mut res = input.lines().map(str::trim_end).join("\n");
+    res.truncate(res.trim_end().len());
+    res
+}
+
+/// Feeds `reader` to `f` one line at a time, normalized like [`normalize`]
+/// does, so that the input never has to be held in memory as a whole.
+///
+/// `f` sees each line as an input of its own; the errors it returns are moved
+/// to the line's position in the stream, and never claim the input ended, as
+/// more lines may follow.
+pub fn for_each_line<R: BufRead>(
+    day: u8,
+    mut reader: R,
+    mut f: impl FnMut(&Source<'_>, &str) -> Result<(), ParseError>,
+) -> Result<(), Error> {
+    let mut call = |line: &str, line_no: usize| {
+        f(&Source::new(day, line), line).map_err(|mut e| {
+            e.line += line_no - 1;
+            e.at_end = false;
+            e
+        })
+    };
+
+    let mut buf = String::new();
+    let mut line_no = 0;
+    // Blank lines are only errors if more input follows them.
+    let mut blank = 0;
+    loop {
+        buf.clear();
+        if reader.read_line(&mut buf).map_err(|e| io_error(&e))? == 0 {
+            return Ok(());
+        }
+        line_no += 1;
+
+        let mut line = buf.trim_end();
+        if line_no == 1 {
+            line = line.strip_prefix(BOM).unwrap_or(line);
+        }
+        if line.is_empty() {
+            blank += 1;
+            continue;
+        }
+
+        for blank_no in line_no - blank..line_no {
+            call("", blank_no)?;
+        }
+        blank = 0;
+        call(line, line_no)?;
+    }
+}
+
+/// Consumes a BOM at the start of `reader`, if there is one.
+pub fn skip_bom<R: BufRead>(reader: &mut R) -> Result<(), Error> {
+    if reader
+        .fill_buf()
+        .map_err(|e| io_error(&e))?
+        .starts_with(BOM.as_bytes())
+    {
+        reader.consume(BOM.len());
+    }
+    Ok(())
+}
+
+pub fn io_error(error: &std::io::Error) -> Error {
+    Error::io(Path::new("<input>"), error)
+}
+
+/// Runs a streaming generator on an input that is already in memory, which
+/// can only fail to parse.
+pub fn from_text<'a, T>(
+    day: u8,
+    input: &'a str,
+    generate: impl FnOnce(&'a [u8]) -> Result<T, Error>,
+) -> Result<T, ParseError> {
+    generate(input.as_bytes()).map_err(|e| match e {
+        Error::Parse(e) => e,
+        e => Source::new(day, input).end_of_input(e.to_string()),
+    })
+}
+
+/// `input` as saved by a Windows editor, with a BOM, CRLF and a final newline.
+#[cfg(test)]
+pub fn windows(input: &str) -> String {
+    format!("\u{feff}{}\r\n", input.replace('\n', "\r\n"))
+}
+
+/// The complete input of a day, used to locate errors in any of its subslices.
+#[derive(Copy, Clone, Debug)]
+pub struct Source<'a> {
+    day: u8,
+    input: &'a str,
+}
+
+impl<'a> Source<'a> {
+    pub const fn new(day: u8, input: &'a str) -> Self {
+        Self { day, input }
+    }
+
+    pub fn error(&self, span: &str, message: impl Into<String>) -> ParseError {
+        ParseError::at(self.day, self.input, span, message)
+    }
+
+    pub fn end_of_input(&self, message: impl Into<String>) -> ParseError {
+        self.error(&self.input[self.input.len()..], message)
+    }
+
+    pub fn number<T: FromStr>(&self, token: &str) -> Result<T, ParseError> {
+        token
+            .parse()
+            .map_err(|_| self.error(token, "expected a number"))
+    }
+
+    pub fn split_once<'b>(
+        &self,
+        text: &'b str,
+        sep: &str,
+    ) -> Result<(&'b str, &'b str), ParseError> {
+        text.split_once(sep)
+            .ok_or_else(|| self.error(text, format!("expected `{sep}`")))
+    }
+
+    /// Splits the input at its first blank line.
+    pub fn sections(&self) -> Result<(&'a str, &'a str), ParseError> {
+        self.input
+            .split_once("\n\n")
+            .ok_or_else(|| self.end_of_input("expected two sections separated by a blank line"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("\u{feff}1 2 \r\n\r\n3\t\r\n\n"), "1 2\n\n3");
+        assert_eq!(normalize(" a\n\nb"), " a\n\nb");
+        assert_eq!(normalize(""), "");
+    }
+
+    #[test]
+    fn test_for_each_line() {
+        let mut lines = vec![];
+        let res = for_each_line(1, &b"\xef\xbb\xbfa \r\n\nb\n\n \n"[..], |_, line| {
+            lines.push(line.to_string());
+            Ok(())
+        });
+        assert_eq!(res, Ok(()));
+        assert_eq!(lines, ["a", "", "b"]);
+
+        let res = for_each_line(1, &b"1\n\n\n2"[..], |src, line| {
+            src.number::<u8>(line).map(drop)
+        });
+        let Err(Error::Parse(e)) = res else {
+            panic!("expected a parse error, got {res:?}");
+        };
+        assert_eq!((e.line, e.column), (2, 1));
+        assert_eq!(
+            e.to_string(),
+            "day 1, line 2, column 1: expected a number (found nothing)"
+        );
+
+        let res = for_each_line(1, &b"1\n2 x"[..], |src, line| {
+            line.split(' ')
+                .try_for_each(|token| src.number::<u8>(token).map(drop))
+        });
+        assert!(
+            matches!(res, Err(Error::Parse(e)) if (e.line, e.column, e.text.as_str()) == (2, 3, "x"))
+        );
+    }
+}
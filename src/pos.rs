@@ -0,0 +1,271 @@
+use crate::grid::Grid;
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
+
+/// One of the four orthogonal directions, with north pointing to row 0.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[must_use]
+pub enum Dir {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Dir {
+    /// All directions, clockwise starting north.
+    pub const ALL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
+
+    pub const fn turn_right(self) -> Self {
+        match self {
+            Self::North => Self::East,
+            Self::East => Self::South,
+            Self::South => Self::West,
+            Self::West => Self::North,
+        }
+    }
+
+    pub const fn turn_left(self) -> Self {
+        self.turn_right().reverse()
+    }
+
+    pub const fn reverse(self) -> Self {
+        match self {
+            Self::North => Self::South,
+            Self::East => Self::West,
+            Self::South => Self::North,
+            Self::West => Self::East,
+        }
+    }
+
+    /// The inverse of parsing from `^>v<`.
+    pub const fn arrow(self) -> char {
+        match self {
+            Self::North => '^',
+            Self::East => '>',
+            Self::South => 'v',
+            Self::West => '<',
+        }
+    }
+
+    pub const fn is_vertical(self) -> bool {
+        matches!(self, Self::North | Self::South)
+    }
+
+    /// The unit offset of one step in this direction.
+    pub const fn delta(self) -> IPos {
+        match self {
+            Self::North => IPos::new(-1, 0),
+            Self::East => IPos::new(0, 1),
+            Self::South => IPos::new(1, 0),
+            Self::West => IPos::new(0, -1),
+        }
+    }
+}
+
+impl TryFrom<char> for Dir {
+    type Error = char;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '^' => Ok(Self::North),
+            '>' => Ok(Self::East),
+            'v' => Ok(Self::South),
+            '<' => Ok(Self::West),
+            c => Err(c),
+        }
+    }
+}
+
+/// A position inside a grid.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pos {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Pos {
+    pub const fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+
+    /// The neighbor in `dir`, unless that would leave the non-negative quadrant.
+    pub fn step(self, dir: Dir) -> Option<Self> {
+        self.offset(dir.delta())
+    }
+
+    /// The neighbor in `dir`, if it lies within `grid`.
+    pub fn step_in<T>(self, dir: Dir, grid: &Grid<T>) -> Option<Self> {
+        self.step(dir)
+            .filter(|next| next.row < grid.rows && next.col < grid.columns)
+    }
+
+    pub fn offset(self, delta: IPos) -> Option<Self> {
+        Some(Self {
+            row: self.row.checked_add_signed(delta.row)?,
+            col: self.col.checked_add_signed(delta.col)?,
+        })
+    }
+
+    pub const fn manhattan(self, other: Self) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+}
+
+impl From<(usize, usize)> for Pos {
+    fn from((row, col): (usize, usize)) -> Self {
+        Self { row, col }
+    }
+}
+
+impl From<Pos> for (usize, usize) {
+    fn from(value: Pos) -> Self {
+        (value.row, value.col)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: Pos) -> &Self::Output {
+        &self[(index.row, index.col)]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, index: Pos) -> &mut Self::Output {
+        &mut self[(index.row, index.col)]
+    }
+}
+
+/// A signed position or offset, for coordinates that may leave the grid.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[must_use]
+pub struct IPos {
+    pub row: isize,
+    pub col: isize,
+}
+
+impl IPos {
+    /// Offsets of the 8-neighborhood, clockwise starting north.
+    pub const ADJACENT: [Self; 8] = [
+        Self::new(-1, 0),
+        Self::new(-1, 1),
+        Self::new(0, 1),
+        Self::new(1, 1),
+        Self::new(1, 0),
+        Self::new(1, -1),
+        Self::new(0, -1),
+        Self::new(-1, -1),
+    ];
+
+    pub const fn new(row: isize, col: isize) -> Self {
+        Self { row, col }
+    }
+
+    pub const fn step(self, dir: Dir) -> Self {
+        let delta = dir.delta();
+        Self::new(self.row + delta.row, self.col + delta.col)
+    }
+
+    pub const fn manhattan(self, other: Self) -> usize {
+        self.row.abs_diff(other.row) + self.col.abs_diff(other.col)
+    }
+}
+
+// Grid dimensions are bounded by the allocation size, which never exceeds `isize::MAX`.
+#[allow(clippy::cast_possible_wrap)]
+impl From<Pos> for IPos {
+    fn from(value: Pos) -> Self {
+        Self::new(value.row as isize, value.col as isize)
+    }
+}
+
+impl TryFrom<IPos> for Pos {
+    type Error = IPos;
+
+    fn try_from(value: IPos) -> Result<Self, Self::Error> {
+        match (usize::try_from(value.row), usize::try_from(value.col)) {
+            (Ok(row), Ok(col)) => Ok(Self { row, col }),
+            _ => Err(value),
+        }
+    }
+}
+
+impl From<IPos> for (isize, isize) {
+    fn from(value: IPos) -> Self {
+        (value.row, value.col)
+    }
+}
+
+impl Add for IPos {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.row + rhs.row, self.col + rhs.col)
+    }
+}
+
+impl Sub for IPos {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.row - rhs.row, self.col - rhs.col)
+    }
+}
+
+impl Neg for IPos {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.row, -self.col)
+    }
+}
+
+impl Mul<isize> for IPos {
+    type Output = Self;
+
+    fn mul(self, rhs: isize) -> Self::Output {
+        Self::new(self.row * rhs, self.col * rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_turns() {
+        assert_eq!(Dir::North.turn_right(), Dir::East);
+        assert_eq!(Dir::North.turn_left(), Dir::West);
+        assert_eq!(Dir::West.reverse(), Dir::East);
+        assert!(Dir::ALL.iter().all(|d| d.turn_left().turn_right() == *d));
+        assert_eq!(Dir::try_from('v'), Ok(Dir::South));
+        assert_eq!(Dir::try_from('x'), Err('x'));
+        assert!(Dir::ALL.iter().all(|d| Dir::try_from(d.arrow()) == Ok(*d)));
+    }
+
+    #[test]
+    fn test_step() {
+        let grid = Grid::new(2, 3, 0);
+        let pos = Pos::new(0, 2);
+
+        assert_eq!(pos.step(Dir::North), None);
+        assert_eq!(pos.step(Dir::East), Some(Pos::new(0, 3)));
+        assert_eq!(pos.step_in(Dir::East, &grid), None);
+        assert_eq!(pos.step_in(Dir::South, &grid), Some(Pos::new(1, 2)));
+        assert_eq!(grid[pos], 0);
+    }
+
+    #[test]
+    fn test_signed() {
+        let a = IPos::from(Pos::new(1, 4));
+        let b = a.step(Dir::North).step(Dir::North);
+
+        assert_eq!(b, IPos::new(-1, 4));
+        assert_eq!(Pos::try_from(b), Err(b));
+        assert_eq!(a - b, Dir::South.delta() * 2);
+        assert_eq!(-(a - b), IPos::new(-2, 0));
+        assert_eq!(a.manhattan(b), 2);
+        assert_eq!(Pos::new(1, 4).manhattan(Pos::new(3, 1)), 5);
+    }
+}
@@ -0,0 +1,743 @@
+use crate::budget::{Budget, CancelToken, Limits};
+use crate::cache::DayCache;
+use crate::error::Error;
+use crate::params::PuzzleParams;
+use crate::search::{self, SearchStats};
+use crate::solver::{Answer, DynSolver, Part, VariantInfo};
+use itertools::Itertools;
+use serde::Serialize;
+use std::any::Any;
+use std::fmt::Write;
+use std::panic::AssertUnwindSafe;
+use std::time::{Duration, Instant};
+
+/// The outcome of parsing and solving one part.
+#[derive(Clone, Debug)]
+pub struct PartRun {
+    pub day: u8,
+    pub part: Part,
+    pub result: Result<Answer, Error>,
+    pub parse_time: Duration,
+    pub solve_time: Duration,
+    /// The work done by the part's searches, if they were recorded.
+    pub search: Option<SearchStats>,
+    /// The entries in the day's cache after solving, on days that memoize.
+    pub cache_size: Option<usize>,
+}
+
+impl PartRun {
+    /// A part that could not be attempted, e.g. because its input is missing.
+    pub const fn failed(day: u8, part: Part, error: Error) -> Self {
+        Self {
+            day,
+            part,
+            result: Err(error),
+            parse_time: Duration::ZERO,
+            solve_time: Duration::ZERO,
+            search: None,
+            cache_size: None,
+        }
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.parse_time + self.solve_time
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Runs `f`, turning a panic into an error.
+fn catch_panic<T>(day: u8, f: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+    std::panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| {
+        Err(Error::Panic {
+            day,
+            message: panic_message(&*payload),
+        })
+    })
+}
+
+/// Parses `input` and solves `part` within `budget`, timing both steps.
+///
+/// `cache` is the day's [`new_cache`](DynSolver::new_cache), if it has one.
+pub fn run_part(
+    solver: &dyn DynSolver,
+    part: Part,
+    input: &str,
+    params: &PuzzleParams,
+    budget: &Budget,
+    mut cache: Option<&mut dyn DayCache>,
+) -> PartRun {
+    let day = solver.day();
+
+    let start = Instant::now();
+    let parsed = catch_panic(day, || solver.parse(part, input));
+    let parse_time = start.elapsed();
+
+    let start = Instant::now();
+    let result = parsed.and_then(|parsed| {
+        catch_panic(day, || {
+            solver.solve_cached(part, &parsed, params, budget, cache.as_deref_mut())
+        })
+    });
+    let solve_time = start.elapsed();
+
+    PartRun {
+        day,
+        part,
+        result,
+        parse_time,
+        solve_time,
+        search: None,
+        cache_size: cache.map(|cache| cache.cache_size()),
+    }
+}
+
+/// The parts of one day to run, with its input or the error from reading it.
+pub struct DayJob {
+    pub solver: &'static dyn DynSolver,
+    pub input: Result<String, Error>,
+    pub params: PuzzleParams,
+    pub parts: Vec<Part>,
+    pub limits: Limits,
+    /// Whether to record the work done by searches.
+    pub search_stats: bool,
+}
+
+impl DayJob {
+    /// Runs the parts in order, sharing the day's cache between them.
+    ///
+    /// Each part gets its own deadline; `token` only stops them all when it is
+    /// cancelled from outside, e.g. by Ctrl-C.
+    pub fn run(&self, token: &CancelToken) -> Vec<PartRun> {
+        let mut cache = self.solver.new_cache();
+
+        self.parts
+            .iter()
+            .map(|&part| match &self.input {
+                Ok(input) => {
+                    let budget = self.limits.start(token.clone());
+                    let cache = cache.as_deref_mut();
+                    let run = || run_part(self.solver, part, input, &self.params, &budget, cache);
+                    if self.search_stats {
+                        let (run, stats) = search::record(run);
+                        PartRun {
+                            search: Some(stats),
+                            ..run
+                        }
+                    } else {
+                        run()
+                    }
+                }
+                Err(e) => PartRun::failed(self.solver.day(), part, e.clone()),
+            })
+            .collect()
+    }
+}
+
+/// Runs all `jobs`, each day on its own thread if `concurrent`.
+///
+/// Failing parts, including panics and timeouts, are recorded and never stop
+/// the others. Cancelling `token`, which all parts share, stops them all.
+/// The runs keep the order of `jobs`.
+pub fn run_all(jobs: &[DayJob], concurrent: bool, token: &CancelToken) -> Vec<PartRun> {
+    if !concurrent {
+        return jobs.iter().flat_map(|job| job.run(token)).collect();
+    }
+
+    std::thread::scope(|scope| {
+        let handles = jobs
+            .iter()
+            .map(|job| scope.spawn(|| job.run(token)))
+            .collect_vec();
+
+        handles
+            .into_iter()
+            .zip(jobs)
+            .flat_map(|(handle, job)| {
+                handle.join().unwrap_or_else(|payload| {
+                    let day = job.solver.day();
+                    let message = panic_message(&*payload);
+                    job.parts
+                        .iter()
+                        .map(|&part| {
+                            let error = Error::Panic {
+                                day,
+                                message: message.clone(),
+                            };
+                            PartRun::failed(day, part, error)
+                        })
+                        .collect()
+                })
+            })
+            .collect()
+    })
+}
+
+/// The outcome of one implementation of a part.
+#[derive(Clone, Debug)]
+pub struct VariantRun {
+    pub name: &'static str,
+    /// Whether the implementation is a heuristic that may get it wrong.
+    pub approximate: bool,
+    pub result: Result<Answer, Error>,
+    pub solve_time: Duration,
+}
+
+/// The name under which [`run_variants`] reports a part's own implementation.
+pub const DEFAULT_VARIANT: &str = "default";
+
+/// Parses `input` once and solves `part` with its own implementation, then
+/// with each of its variants.
+pub fn run_variants(
+    solver: &dyn DynSolver,
+    part: Part,
+    input: &str,
+    params: &PuzzleParams,
+) -> Result<Vec<VariantRun>, Error> {
+    let day = solver.day();
+    let parsed = catch_panic(day, || solver.parse(part, input))?;
+
+    let timed = |info: VariantInfo, solve: &dyn Fn() -> Result<Answer, Error>| {
+        let start = Instant::now();
+        let result = catch_panic(day, solve);
+        VariantRun {
+            name: info.name,
+            approximate: info.approximate,
+            result,
+            solve_time: start.elapsed(),
+        }
+    };
+
+    let default = VariantInfo {
+        name: DEFAULT_VARIANT,
+        approximate: false,
+    };
+    let mut runs = vec![timed(default, &|| solver.solve_with(part, &parsed, params))];
+    for info in solver.variants(part) {
+        runs.push(timed(info, &|| {
+            solver.solve_variant(part, info.name, &parsed, params)
+        }));
+    }
+    Ok(runs)
+}
+
+/// The runs whose result differs from that of the first run.
+fn differing(runs: &[VariantRun]) -> Vec<&VariantRun> {
+    let Some((first, rest)) = runs.split_first() else {
+        return vec![];
+    };
+    rest.iter()
+        .filter(|run| run.result != first.result)
+        .collect()
+}
+
+/// The variants whose result differs from that of the first run, leaving
+/// out the approximate ones.
+pub fn disagreeing(runs: &[VariantRun]) -> Vec<&VariantRun> {
+    differing(runs)
+        .into_iter()
+        .filter(|run| !run.approximate)
+        .collect()
+}
+
+/// Renders `runs` side by side, with each one's speedup over the first run
+/// and the variants that disagree with it marked.
+pub fn variant_report(runs: &[VariantRun]) -> String {
+    let differing = differing(runs);
+    let first_time = runs.first().map_or(Duration::ZERO, |run| run.solve_time);
+    let width = runs.iter().map(|run| run.name.len()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for run in runs {
+        let answer = match &run.result {
+            Ok(answer) => answer.to_string(),
+            Err(e) => format!("FAILED, {e}"),
+        };
+        let speed = if run.solve_time.is_zero() {
+            String::new()
+        } else {
+            format!(
+                " {:.2}x",
+                first_time.as_secs_f64() / run.solve_time.as_secs_f64()
+            )
+        };
+        let mark = match differing.iter().find(|other| std::ptr::eq(**other, run)) {
+            Some(run) if run.approximate => "  differs (approximate)",
+            Some(_) => "  DISAGREES",
+            None => "",
+        };
+        let _ = writeln!(
+            out,
+            "  {:<width$}  {:>10}{speed}  {answer}{mark}",
+            run.name,
+            time_cell(Some(run.solve_time)),
+        );
+    }
+    out
+}
+
+/// How many of the slowest days the report marks.
+const SLOWEST: usize = 3;
+
+/// Answers longer than this are cut short in the report.
+const ANSWER_WIDTH: usize = 20;
+
+fn answer_cell(run: Option<&PartRun>) -> String {
+    let Some(run) = run else {
+        return String::new();
+    };
+
+    let answer = match &run.result {
+        Ok(answer) => answer,
+        Err(Error::GaveUp { .. }) => return "GAVE UP".to_string(),
+        Err(_) => return "FAILED".to_string(),
+    };
+
+    let answer = answer.to_string();
+    if answer.chars().count() > ANSWER_WIDTH {
+        answer.chars().take(ANSWER_WIDTH - 1).chain(['…']).collect()
+    } else {
+        answer
+    }
+}
+
+fn time_cell(time: Option<Duration>) -> String {
+    time.map_or_else(String::new, |time| format!("{time:.2?}"))
+}
+
+/// Renders `runs` as a table with one row per day, marking the slowest days
+/// with `*`, followed by a summary and the failed parts.
+pub fn report(runs: &[PartRun], wall_time: Duration) -> String {
+    let days = runs.iter().chunk_by(|run| run.day);
+    let days = days
+        .into_iter()
+        .map(|(day, runs)| (day, runs.collect_vec()))
+        .collect_vec();
+
+    let day_time = |runs: &[&PartRun]| runs.iter().map(|run| run.total_time()).sum::<Duration>();
+    let slowest = days
+        .iter()
+        .sorted_by_key(|(_, runs)| std::cmp::Reverse(day_time(runs)))
+        .take(SLOWEST)
+        .map(|(day, _)| *day)
+        .collect_vec();
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "  day  {:<ANSWER_WIDTH$}  {:<ANSWER_WIDTH$}  {:>10}  {:>10}  {:>10}  {:>10}",
+        "part 1", "part 2", "parse", "part 1", "part 2", "total"
+    );
+
+    for (day, runs) in &days {
+        let part = |part| runs.iter().copied().find(|run| run.part == part);
+        let (one, two) = (part(Part::One), part(Part::Two));
+
+        let _ = writeln!(
+            out,
+            "{} {day:>3}  {:<ANSWER_WIDTH$}  {:<ANSWER_WIDTH$}  {:>10}  {:>10}  {:>10}  {:>10}",
+            if slowest.contains(day) { '*' } else { ' ' },
+            answer_cell(one),
+            answer_cell(two),
+            time_cell(Some(runs.iter().map(|run| run.parse_time).sum())),
+            time_cell(one.map(|run| run.solve_time)),
+            time_cell(two.map(|run| run.solve_time)),
+            time_cell(Some(day_time(runs))),
+        );
+    }
+
+    let total = runs.iter().map(PartRun::total_time).sum::<Duration>();
+    let failed = runs.iter().filter(|run| run.result.is_err()).collect_vec();
+    let unanswered = runs
+        .iter()
+        .filter(|run| run.result.as_ref().is_ok_and(|a| *a == Answer::None))
+        .count();
+
+    let _ = writeln!(
+        out,
+        "\n{} parts: {} answered, {unanswered} without answer, {} failed; \
+         {total:.2?} total, {wall_time:.2?} wall time",
+        runs.len(),
+        runs.len() - unanswered - failed.len(),
+        failed.len(),
+    );
+    for run in failed {
+        if let Err(e) = &run.result {
+            let _ = writeln!(out, "day {:02} part {}: {e}", run.day, run.part);
+        }
+    }
+
+    let searched = runs
+        .iter()
+        .filter_map(|run| Some((run, run.search.filter(|stats| stats.searches > 0)?)))
+        .collect_vec();
+    if !searched.is_empty() {
+        let _ = writeln!(out, "\nsearches:");
+    }
+    for (run, stats) in searched {
+        let _ = writeln!(out, "day {:02} part {}: {stats}", run.day, run.part);
+    }
+
+    let cached = runs
+        .iter()
+        .filter_map(|run| Some((run, run.cache_size?)))
+        .collect_vec();
+    if !cached.is_empty() {
+        let _ = writeln!(out, "\ncaches:");
+    }
+    for (run, size) in cached {
+        let _ = writeln!(out, "day {:02} part {}: {size} entries", run.day, run.part);
+    }
+
+    out
+}
+
+/// One entry of the JSON results document.
+#[derive(Serialize)]
+struct Record<'a> {
+    day: u8,
+    part: u8,
+    answer: Option<&'a Answer>,
+    kind: &'static str,
+    parse_ns: u128,
+    solve_ns: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    search: Option<SearchStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_size: Option<usize>,
+}
+
+impl<'a> From<&'a PartRun> for Record<'a> {
+    fn from(run: &'a PartRun) -> Self {
+        Self {
+            day: run.day,
+            part: run.part.number(),
+            answer: run.result.as_ref().ok(),
+            kind: run.result.as_ref().map_or("error", Answer::kind),
+            parse_ns: run.parse_time.as_nanos(),
+            solve_ns: run.solve_time.as_nanos(),
+            error: run.result.as_ref().err().map(ToString::to_string),
+            search: run.search,
+            cache_size: run.cache_size,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    results: Vec<Record<'a>>,
+}
+
+/// Renders `runs` as a JSON document with one entry per part.
+pub fn to_json(runs: &[PartRun]) -> String {
+    let report = Report {
+        results: runs.iter().map(Record::from).collect(),
+    };
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::budget::Exhausted;
+    use crate::gen;
+    use crate::solver::{self, Solver, Variant};
+
+    struct Panicky;
+
+    impl Solver for Panicky {
+        const DAY: u8 = 3;
+
+        type Input = ();
+
+        const VARIANTS: &'static [Variant<Self::Input>] = &[
+            Variant {
+                name: "wrong",
+                part: Part::Two,
+                approximate: false,
+                solve: |(), _| Ok(Answer::Int(1)),
+            },
+            Variant {
+                name: "guess",
+                part: Part::Two,
+                approximate: true,
+                solve: |(), _| Ok(Answer::Int(2)),
+            },
+        ];
+
+        fn parse(&self, _input: &str) -> Result<Self::Input, Error> {
+            Ok(())
+        }
+
+        fn part1(&self, _input: &Self::Input) -> Answer {
+            panic!("out of cheese")
+        }
+
+        fn part2(&self, _input: &Self::Input) -> Answer {
+            Answer::None
+        }
+    }
+
+    /// Iterates until its budget runs out.
+    struct Endless;
+
+    impl Solver for Endless {
+        const DAY: u8 = 14;
+
+        type Input = ();
+
+        fn parse(&self, _input: &str) -> Result<Self::Input, Error> {
+            Ok(())
+        }
+
+        fn part1(&self, _input: &Self::Input) -> Answer {
+            Answer::None
+        }
+
+        fn part2(&self, _input: &Self::Input) -> Answer {
+            Answer::None
+        }
+
+        fn part1_within(
+            &self,
+            _input: &Self::Input,
+            _params: &PuzzleParams,
+            budget: &Budget,
+        ) -> Result<Answer, Error> {
+            let reason = loop {
+                if let Err(reason) = budget.step() {
+                    break reason;
+                }
+            };
+            Err(Error::GaveUp { day: 14, reason })
+        }
+    }
+
+    #[test]
+    fn test_to_json() {
+        let day01 = solver::solver(1).expect("registered");
+        let runs = [
+            run_part(
+                day01,
+                Part::One,
+                "1   3\n2   5",
+                &PuzzleParams::new(),
+                &Budget::unlimited(),
+                None,
+            ),
+            PartRun::failed(2, Part::Two, Error::UnknownDay(2)),
+        ];
+
+        let json: serde_json::Value = serde_json::from_str(&to_json(&runs)).expect("valid JSON");
+        let results = &json["results"];
+
+        assert_eq!(results[0]["day"], 1);
+        assert_eq!(results[0]["answer"], 5);
+        assert_eq!(results[0]["kind"], "int");
+        assert!(results[0]["parse_ns"].is_u64());
+        assert!(results[0].get("error").is_none());
+        assert_eq!(results[1]["answer"], serde_json::Value::Null);
+        assert_eq!(results[1]["kind"], "error");
+        assert_eq!(results[1]["error"], "no solver registered for day 2");
+    }
+
+    #[test]
+    fn test_run_all() {
+        let day01 = solver::solver(1).expect("registered");
+        let jobs = [
+            DayJob {
+                solver: &Panicky,
+                input: Ok(String::new()),
+                params: PuzzleParams::new(),
+                parts: Part::ALL.to_vec(),
+                limits: Limits::default(),
+                search_stats: false,
+            },
+            DayJob {
+                solver: day01,
+                input: Ok("1   3\n2   5".to_string()),
+                params: PuzzleParams::new(),
+                parts: vec![Part::One],
+                limits: Limits::default(),
+                search_stats: true,
+            },
+        ];
+
+        for concurrent in [false, true] {
+            let runs = run_all(&jobs, concurrent, &CancelToken::new());
+            let results = runs.iter().map(|run| (run.day, run.result.clone()));
+
+            assert!(results.eq([
+                (
+                    3,
+                    Err(Error::Panic {
+                        day: 3,
+                        message: "out of cheese".to_string()
+                    })
+                ),
+                (3, Ok(Answer::None)),
+                (1, Ok(Answer::Int(5))),
+            ]));
+            assert_eq!(runs[0].search, None);
+            assert_eq!(runs[2].search, Some(SearchStats::default()));
+        }
+    }
+
+    #[test]
+    fn test_timeout_isolated() {
+        let job = |timeout, max_steps| DayJob {
+            solver: &Endless,
+            input: Ok(String::new()),
+            params: PuzzleParams::new(),
+            parts: vec![Part::One],
+            limits: Limits { timeout, max_steps },
+            search_stats: false,
+        };
+        let jobs = [job(Some(Duration::ZERO), None), job(None, Some(1000))];
+
+        for concurrent in [false, true] {
+            let token = CancelToken::new();
+            let runs = run_all(&jobs, concurrent, &token);
+            let reasons = runs.iter().map(|run| match run.result {
+                Err(Error::GaveUp { reason, .. }) => Some(reason),
+                _ => None,
+            });
+
+            assert!(reasons.eq([Some(Exhausted::Timeout), Some(Exhausted::Steps(1000))]));
+            assert!(!token.is_cancelled());
+        }
+
+        let token = CancelToken::new();
+        token.cancel();
+        let runs = run_all(&jobs[1..], false, &token);
+        assert!(matches!(
+            runs[0].result,
+            Err(Error::GaveUp {
+                reason: Exhausted::Cancelled,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_shared_cache() {
+        let job = DayJob {
+            solver: solver::solver(11).expect("registered"),
+            input: Ok("125 17".to_string()),
+            params: PuzzleParams::new(),
+            parts: vec![Part::Two, Part::One],
+            limits: Limits::default(),
+            search_stats: false,
+        };
+
+        let runs = job.run(&CancelToken::new());
+        assert_eq!(runs[1].result, Ok(Answer::Int(55312)));
+        let (Some(two), Some(one)) = (runs[0].cache_size, runs[1].cache_size) else {
+            panic!("day 11 has a cache");
+        };
+        // Part 1 adds its entries to those of part 2 instead of starting over.
+        assert!(one > two);
+    }
+
+    #[test]
+    fn test_report() {
+        let run = |day, part, result, ms| PartRun {
+            day,
+            part,
+            result,
+            parse_time: Duration::ZERO,
+            solve_time: Duration::from_millis(ms),
+            search: None,
+            cache_size: None,
+        };
+        let mut runs = [
+            run(1, Part::One, Ok(Answer::Int(1)), 1),
+            run(1, Part::Two, Ok(Answer::None), 1),
+            run(2, Part::One, Err(Error::UnknownDay(2)), 0),
+            run(3, Part::One, Ok(Answer::Text("x".repeat(30))), 5),
+            run(4, Part::One, Ok(Answer::Int(4)), 3),
+            run(5, Part::One, Ok(Answer::Int(5)), 4),
+        ];
+
+        runs[4].search = Some(SearchStats {
+            searches: 2,
+            expanded: 10,
+            successor_calls: 12,
+            peak_frontier: 3,
+            time_ns: 1_500_000,
+        });
+        runs[5].search = Some(SearchStats::default());
+
+        let report = report(&runs, Duration::from_millis(14));
+        let lines = report.lines().collect_vec();
+
+        assert!(lines[1].starts_with("    1  1 "));
+        assert!(lines[3].starts_with("*   3  xxxxxxxxxxxxxxxxxxx… "));
+        assert!(lines[4].starts_with("*   4"));
+        assert!(lines[5].starts_with("*   5"));
+        assert_eq!(
+            lines[7],
+            "6 parts: 4 answered, 1 without answer, 1 failed; 14.00ms total, 14.00ms wall time"
+        );
+        assert_eq!(lines[8], "day 02 part 1: no solver registered for day 2");
+        assert_eq!(lines[10], "searches:");
+        assert_eq!(
+            lines[11],
+            "day 04 part 1: 2 searches, 10 expanded, 12 successor calls, peak frontier 3, 1.50ms"
+        );
+        assert_eq!(lines.len(), 12);
+    }
+
+    #[test]
+    fn test_variants() {
+        let runs = run_variants(&Panicky, Part::Two, "", &PuzzleParams::new()).expect("parses");
+        let disagreeing = disagreeing(&runs);
+        assert_eq!(disagreeing.len(), 1);
+        assert_eq!(disagreeing[0].name, "wrong");
+
+        let report = variant_report(&runs);
+        let lines = report.lines().collect_vec();
+        assert!(lines[0].starts_with("  default "));
+        assert!(lines[0].ends_with(" (no answer)"));
+        assert!(lines[1].starts_with("  wrong   "));
+        assert!(lines[1].ends_with(" 1  DISAGREES"));
+        assert!(lines[2].starts_with("  guess   "));
+        assert!(lines[2].ends_with(" 2  differs (approximate)"));
+
+        let runs = run_variants(&Panicky, Part::One, "", &PuzzleParams::new()).expect("parses");
+        assert_eq!(runs.len(), 1);
+        assert!(matches!(runs[0].result, Err(Error::Panic { .. })));
+    }
+
+    #[test]
+    fn test_variants_agree() {
+        for solver in solver::solvers() {
+            for part in Part::ALL {
+                if solver.variants(part).is_empty() {
+                    continue;
+                }
+
+                for seed in 0..4 {
+                    let input = gen::generate(solver.day(), 30, seed).expect("registered day");
+                    let runs = run_variants(*solver, part, &input, &PuzzleParams::new())
+                        .expect("generated input parses");
+                    assert!(runs.len() > 1);
+                    assert!(
+                        disagreeing(&runs).is_empty(),
+                        "day {} part {part}, seed {seed}:\n{}",
+                        solver.day(),
+                        variant_report(&runs)
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,340 @@
+//! Instrumented wrappers around the `pathfinding` searches the days use.
+//!
+//! The wrappers behave like the functions they wrap. Inside [`record`] they
+//! also count the work each search does, which shows where a better
+//! heuristic or a plain BFS would pay off. Searches on other threads are
+//! counted once they [`enter`] the recording [`scope`], which [`par`](crate::par)
+//! does for its workers.
+
+use itertools::Either;
+use pathfinding::num_traits::Zero;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    static SCOPE: RefCell<Option<Scope>> = const { RefCell::new(None) };
+}
+
+/// The totals of one [`record`], shared by every thread that searches for it.
+#[derive(Clone, Debug, Default)]
+pub struct Scope(Arc<Totals>);
+
+#[derive(Debug, Default)]
+struct Totals {
+    searches: AtomicU64,
+    expanded: AtomicU64,
+    successor_calls: AtomicU64,
+    peak_frontier: AtomicU64,
+    time_ns: AtomicU64,
+}
+
+impl Scope {
+    fn add(&self, stats: &SearchStats) {
+        let totals = &self.0;
+        totals.searches.fetch_add(stats.searches, Ordering::Relaxed);
+        totals.expanded.fetch_add(stats.expanded, Ordering::Relaxed);
+        totals
+            .successor_calls
+            .fetch_add(stats.successor_calls, Ordering::Relaxed);
+        totals
+            .peak_frontier
+            .fetch_max(stats.peak_frontier, Ordering::Relaxed);
+        let _ = totals
+            .time_ns
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |time| {
+                Some(time.saturating_add(stats.time_ns))
+            });
+    }
+
+    fn stats(&self) -> SearchStats {
+        let totals = &self.0;
+        SearchStats {
+            searches: totals.searches.load(Ordering::Relaxed),
+            expanded: totals.expanded.load(Ordering::Relaxed),
+            successor_calls: totals.successor_calls.load(Ordering::Relaxed),
+            peak_frontier: totals.peak_frontier.load(Ordering::Relaxed),
+            time_ns: totals.time_ns.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// The scope this thread records into, if any.
+pub fn scope() -> Option<Scope> {
+    SCOPE.with_borrow(Clone::clone)
+}
+
+/// Runs `f` recording into `scope`, e.g. one taken from the thread that
+/// handed this one its work.
+pub fn enter<T>(scope: Option<&Scope>, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Scope>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPE.set(self.0.take());
+        }
+    }
+
+    let _outer = Restore(SCOPE.replace(scope.cloned()));
+    f()
+}
+
+/// The work done by the searches of one solve.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct SearchStats {
+    pub searches: u64,
+    /// Distinct nodes whose successors were asked for.
+    pub expanded: u64,
+    pub successor_calls: u64,
+    /// The most nodes seen but not yet expanded at once, over all searches.
+    pub peak_frontier: u64,
+    /// Time spent searching, bookkeeping included.
+    pub time_ns: u64,
+}
+
+impl SearchStats {
+    pub const fn time(&self) -> Duration {
+        Duration::from_nanos(self.time_ns)
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} searches, {} expanded, {} successor calls, peak frontier {}, {:.2?}",
+            self.searches,
+            self.expanded,
+            self.successor_calls,
+            self.peak_frontier,
+            self.time()
+        )
+    }
+}
+
+/// Runs `f`, returning the work done by the searches it made.
+pub fn record<T>(f: impl FnOnce() -> T) -> (T, SearchStats) {
+    let inner = Scope::default();
+    let result = enter(Some(&inner), f);
+    let stats = inner.stats();
+
+    if let Some(outer) = scope() {
+        outer.add(&stats);
+    }
+    (result, stats)
+}
+
+/// Watches the successor calls of one search, if recording.
+struct Probe<N> {
+    enabled: bool,
+    seen: HashSet<N>,
+    expanded: HashSet<N>,
+    successor_calls: u64,
+    peak_frontier: usize,
+}
+
+impl<N: Eq + Hash + Clone> Probe<N> {
+    /// `successors`, untouched unless recording.
+    fn expand<I: IntoIterator>(
+        &mut self,
+        node: &N,
+        successors: I,
+        key: impl Fn(&I::Item) -> &N,
+    ) -> Either<I::IntoIter, std::vec::IntoIter<I::Item>> {
+        if !self.enabled {
+            return Either::Left(successors.into_iter());
+        }
+        let successors = successors.into_iter().collect::<Vec<_>>();
+
+        self.successor_calls += 1;
+        self.seen.insert(node.clone());
+        self.expanded.insert(node.clone());
+        self.seen.extend(successors.iter().map(|s| key(s).clone()));
+        self.peak_frontier = self
+            .peak_frontier
+            .max(self.seen.len() - self.expanded.len());
+
+        Either::Right(successors.into_iter())
+    }
+
+    fn stats(&self, time: Duration) -> SearchStats {
+        SearchStats {
+            searches: 1,
+            expanded: self.expanded.len() as u64,
+            successor_calls: self.successor_calls,
+            peak_frontier: self.peak_frontier as u64,
+            time_ns: u64::try_from(time.as_nanos()).unwrap_or(u64::MAX),
+        }
+    }
+}
+
+fn instrument<N, T>(start: Option<&N>, search: impl FnOnce(&mut Probe<N>) -> T) -> T
+where
+    N: Eq + Hash + Clone,
+{
+    let scope = scope();
+    let enabled = scope.is_some();
+    let mut probe = Probe {
+        enabled,
+        seen: start.into_iter().filter(|_| enabled).cloned().collect(),
+        expanded: HashSet::new(),
+        successor_calls: 0,
+        peak_frontier: 0,
+    };
+
+    let started = Instant::now();
+    let result = search(&mut probe);
+
+    if let Some(scope) = scope {
+        scope.add(&probe.stats(started.elapsed()));
+    }
+    result
+}
+
+pub fn dijkstra<N, C, IN>(
+    start: &N,
+    mut successors: impl FnMut(&N) -> IN,
+    success: impl FnMut(&N) -> bool,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Zero + Ord + Copy,
+    IN: IntoIterator<Item = (N, C)>,
+{
+    instrument(Some(start), |probe| {
+        pathfinding::prelude::dijkstra(
+            start,
+            |node| probe.expand(node, successors(node), |(n, _)| n),
+            success,
+        )
+    })
+}
+
+pub fn dijkstra_all<N, C, IN>(
+    start: &N,
+    mut successors: impl FnMut(&N) -> IN,
+) -> std::collections::HashMap<N, (N, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Zero + Ord + Copy,
+    IN: IntoIterator<Item = (N, C)>,
+{
+    instrument(Some(start), |probe| {
+        pathfinding::prelude::dijkstra_all(start, |node| {
+            probe.expand(node, successors(node), |(n, _)| n)
+        })
+    })
+}
+
+pub fn astar_bag_collect<N, C, IN>(
+    start: &N,
+    mut successors: impl FnMut(&N) -> IN,
+    heuristic: impl FnMut(&N) -> C,
+    success: impl FnMut(&N) -> bool,
+) -> Option<(Vec<Vec<N>>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Zero + Ord + Copy,
+    IN: IntoIterator<Item = (N, C)>,
+{
+    instrument(Some(start), |probe| {
+        pathfinding::prelude::astar_bag_collect(
+            start,
+            |node| probe.expand(node, successors(node), |(n, _)| n),
+            heuristic,
+            success,
+        )
+    })
+}
+
+pub fn count_paths<N, IN>(
+    start: N,
+    mut successors: impl FnMut(&N) -> IN,
+    success: impl FnMut(&N) -> bool,
+) -> usize
+where
+    N: Eq + Hash + Clone,
+    IN: IntoIterator<Item = N>,
+{
+    instrument(Some(&start.clone()), |probe| {
+        pathfinding::prelude::count_paths(
+            start,
+            |node| probe.expand(node, successors(node), |n| n),
+            success,
+        )
+    })
+}
+
+pub fn strongly_connected_components<N, IN>(
+    nodes: &[N],
+    mut successors: impl FnMut(&N) -> IN,
+) -> Vec<Vec<N>>
+where
+    N: Eq + Hash + Clone,
+    IN: IntoIterator<Item = N>,
+{
+    instrument(None, |probe| {
+        pathfinding::prelude::strongly_connected_components(nodes, |node| {
+            probe.expand(node, successors(node), |n| n)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(n: u32) -> Vec<(u32, u32)> {
+        [n + 1, n + 2]
+            .into_iter()
+            .filter(|&m| m <= 10)
+            .map(|m| (m, 1))
+            .collect()
+    }
+
+    #[test]
+    fn test_record() {
+        assert_eq!(
+            dijkstra(&0, |&n| line(n), |&n| n == 4),
+            Some((vec![0, 2, 4], 2))
+        );
+
+        let (path, stats) = record(|| dijkstra(&0, |&n| line(n), |&n| n == 4));
+        assert_eq!(path, Some((vec![0, 2, 4], 2)));
+        assert_eq!(stats.searches, 1);
+        assert_eq!(stats.expanded, stats.successor_calls);
+        assert!((4..=5).contains(&stats.expanded));
+        assert!(stats.peak_frontier >= 2);
+
+        let ((count, inner), outer) = record(|| {
+            record(|| count_paths(0, |&n| line(n).into_iter().map(|(m, _)| m), |&n| n == 10))
+        });
+        assert_eq!(count, 89);
+        assert_eq!((inner.searches, inner.expanded), (1, 10));
+        assert_eq!(outer, inner);
+
+        let ((), stats) = record(|| ());
+        assert_eq!(stats, SearchStats::default());
+    }
+
+    #[test]
+    fn test_record_threads() {
+        let search = || count_paths(0, |&n| line(n).into_iter().map(|(m, _)| m), |&n| n == 10);
+        let (counts, stats) = record(|| {
+            let scope = scope();
+            std::thread::scope(|threads| {
+                let handles: [_; 3] =
+                    std::array::from_fn(|_| threads.spawn(|| enter(scope.as_ref(), search)));
+                handles.map(|handle| handle.join().expect("no panic"))
+            })
+        });
+
+        assert_eq!(counts, [89; 3]);
+        assert_eq!((stats.searches, stats.expanded), (3, 30));
+    }
+}
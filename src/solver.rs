@@ -0,0 +1,572 @@
+use crate::budget::Budget;
+use crate::cache::DayCache;
+use crate::dot::Graph;
+use crate::error::Error;
+use crate::params::PuzzleParams;
+use crate::parse;
+use crate::viz::FrameSink;
+use crate::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day12, day13,
+    day14, day15, day16, day17, day18, day19, day20, day21, day22, day23, day24, day25,
+};
+use serde::{Serialize, Serializer};
+use std::any::Any;
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Self; 2] = [Self::One, Self::Two];
+
+    pub const fn number(self) -> u8 {
+        match self {
+            Self::One => 1,
+            Self::Two => 2,
+        }
+    }
+}
+
+impl TryFrom<u8> for Part {
+    type Error = Error;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Self::One),
+            2 => Ok(Self::Two),
+            _ => Err(Error::UnknownPart(value)),
+        }
+    }
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.number())
+    }
+}
+
+/// The result of a single part, independent of the type the solver computes it in.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i128),
+    Text(String),
+    None,
+}
+
+impl Answer {
+    /// A short name for the variant, as used in machine-readable output.
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Int(_) => "int",
+            Self::Text(_) => "text",
+            Self::None => "none",
+        }
+    }
+}
+
+/// Integers serialize as numbers, text as strings and `None` as null.
+impl Serialize for Answer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Int(n) => serializer.serialize_i128(*n),
+            Self::Text(s) => serializer.serialize_str(s),
+            Self::None => serializer.serialize_none(),
+        }
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(n) => write!(f, "{n}"),
+            Self::Text(s) => write!(f, "{s}"),
+            Self::None => write!(f, "(no answer)"),
+        }
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(value: usize) -> Self {
+        Self::Int(value as i128)
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(value: i64) -> Self {
+        Self::Int(i128::from(value))
+    }
+}
+
+/// Falls back to text for integers that do not fit the `Int` variant.
+impl From<num::BigInt> for Answer {
+    fn from(value: num::BigInt) -> Self {
+        i128::try_from(&value).map_or_else(|_| Self::Text(value.to_string()), Self::Int)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Self::Text(value)
+    }
+}
+
+impl<T: Into<Self>> From<Option<T>> for Answer {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Self::None, Into::into)
+    }
+}
+
+/// An alternative implementation of one part, such as a plain reference
+/// solution kept next to an optimized one.
+///
+/// It must agree with the part, reading the same [`PuzzleParams`] as
+/// `part1_with`/`part2_with`, unless it is `approximate`: a heuristic kept to
+/// show where it goes wrong.
+pub struct Variant<I: 'static> {
+    pub name: &'static str,
+    pub part: Part,
+    pub approximate: bool,
+    pub solve: fn(&I, &PuzzleParams) -> Result<Answer, Error>,
+}
+
+/// A [`Variant`] without its input type, as listed by [`DynSolver::variants`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VariantInfo {
+    pub name: &'static str,
+    pub approximate: bool,
+}
+
+/// A puzzle solution: a generator plus one function per part.
+///
+/// Days whose parts need differently prepared input override `parse_part2`.
+/// Days with input-specific constants list them in `PARAMS` and read them in
+/// `part1_with`/`part2_with`, falling back to the real puzzle's values.
+/// Days with several implementations of a part list the others in `VARIANTS`.
+pub trait Solver {
+    const DAY: u8;
+
+    /// Names of the [`PuzzleParams`] this day understands.
+    const PARAMS: &'static [&'static str] = &[];
+
+    const VARIANTS: &'static [Variant<Self::Input>] = &[];
+
+    type Input: 'static;
+
+    fn parse(&self, input: &str) -> Result<Self::Input, Error>;
+
+    fn parse_part2(&self, input: &str) -> Result<Self::Input, Error> {
+        self.parse(input)
+    }
+
+    fn part1(&self, input: &Self::Input) -> Answer;
+
+    fn part2(&self, input: &Self::Input) -> Answer;
+
+    fn part1_with(&self, input: &Self::Input, _params: &PuzzleParams) -> Result<Answer, Error> {
+        Ok(self.part1(input))
+    }
+
+    fn part2_with(&self, input: &Self::Input, _params: &PuzzleParams) -> Result<Answer, Error> {
+        Ok(self.part2(input))
+    }
+
+    /// Like `part1_with`, for days that iterate until something happens.
+    fn part1_within(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        _budget: &Budget,
+    ) -> Result<Answer, Error> {
+        self.part1_with(input, params)
+    }
+
+    fn part2_within(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        _budget: &Budget,
+    ) -> Result<Answer, Error> {
+        self.part2_with(input, params)
+    }
+
+    /// Memoization for `part1_cached`/`part2_cached` that the runner keeps
+    /// between solves, on days that memoize.
+    fn new_cache(&self) -> Option<Box<dyn DayCache>> {
+        None
+    }
+
+    /// Like `part1_within`, reusing what `cache` from [`new_cache`](Self::new_cache)
+    /// remembers.
+    fn part1_cached(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        budget: &Budget,
+        _cache: &mut dyn DayCache,
+    ) -> Result<Answer, Error> {
+        self.part1_within(input, params, budget)
+    }
+
+    fn part2_cached(
+        &self,
+        input: &Self::Input,
+        params: &PuzzleParams,
+        budget: &Budget,
+        _cache: &mut dyn DayCache,
+    ) -> Result<Answer, Error> {
+        self.part2_within(input, params, budget)
+    }
+
+    /// Solves `part` while emitting frames of the simulation to `sink`.
+    fn visualize(
+        &self,
+        _input: &Self::Input,
+        _part: Part,
+        _params: &PuzzleParams,
+        _sink: &mut dyn FrameSink,
+    ) -> Result<Answer, Error> {
+        Err(Error::NoVisualization { day: Self::DAY })
+    }
+
+    /// The puzzle's graph, highlighting what makes up the answer to `part`.
+    fn graph(
+        &self,
+        _input: &Self::Input,
+        _part: Part,
+        _params: &PuzzleParams,
+    ) -> Result<Graph, Error> {
+        Err(Error::NoGraph { day: Self::DAY })
+    }
+
+    /// The assumptions `part` makes about its input that `input` breaks, in
+    /// plain language. Such an input may get a wrong answer rather than none.
+    fn validate(&self, _input: &Self::Input, _part: Part) -> Vec<String> {
+        vec![]
+    }
+}
+
+/// Input produced by [`DynSolver::parse`], to be handed back to the same solver.
+pub struct Parsed(Box<dyn Any>);
+
+/// Type-erased [`Solver`], so that all days can live in one registry.
+pub trait DynSolver: Sync {
+    fn day(&self) -> u8;
+
+    fn params(&self) -> &'static [&'static str];
+
+    /// Parses `input` after [normalizing](crate::parse::normalize) it.
+    fn parse(&self, part: Part, input: &str) -> Result<Parsed, Error> {
+        self.parse_raw(part, &parse::normalize(input))
+    }
+
+    /// Parses `input` exactly as given.
+    fn parse_raw(&self, part: Part, input: &str) -> Result<Parsed, Error>;
+
+    /// Fails if `parsed` was not produced by this solver.
+    fn solve(&self, part: Part, parsed: &Parsed) -> Result<Answer, Error> {
+        self.solve_with(part, parsed, &PuzzleParams::default())
+    }
+
+    /// Fails if `parsed` was not produced by this solver or `params` names a
+    /// parameter the day does not understand.
+    fn solve_with(
+        &self,
+        part: Part,
+        parsed: &Parsed,
+        params: &PuzzleParams,
+    ) -> Result<Answer, Error> {
+        self.solve_within(part, parsed, params, &Budget::unlimited())
+    }
+
+    /// Like [`solve_with`](Self::solve_with), giving up once `budget` is spent.
+    fn solve_within(
+        &self,
+        part: Part,
+        parsed: &Parsed,
+        params: &PuzzleParams,
+        budget: &Budget,
+    ) -> Result<Answer, Error>;
+
+    /// See [`Solver::new_cache`].
+    fn new_cache(&self) -> Option<Box<dyn DayCache>>;
+
+    /// Like [`solve_within`](Self::solve_within), reusing `cache` if given.
+    fn solve_cached(
+        &self,
+        part: Part,
+        parsed: &Parsed,
+        params: &PuzzleParams,
+        budget: &Budget,
+        cache: Option<&mut dyn DayCache>,
+    ) -> Result<Answer, Error>;
+
+    /// Like [`solve_with`](Self::solve_with), emitting frames to `sink` on days
+    /// that simulate something.
+    fn visualize(
+        &self,
+        part: Part,
+        parsed: &Parsed,
+        params: &PuzzleParams,
+        sink: &mut dyn FrameSink,
+    ) -> Result<Answer, Error>;
+
+    /// The graph of days whose puzzle is one, see [`Solver::graph`].
+    fn graph(&self, part: Part, parsed: &Parsed, params: &PuzzleParams) -> Result<Graph, Error>;
+
+    /// The alternative implementations of `part`, see [`Variant`].
+    fn variants(&self, part: Part) -> Vec<VariantInfo>;
+
+    /// Solves `part` with the alternative implementation `name`.
+    fn solve_variant(
+        &self,
+        part: Part,
+        name: &str,
+        parsed: &Parsed,
+        params: &PuzzleParams,
+    ) -> Result<Answer, Error>;
+
+    /// See [`Solver::validate`].
+    fn validate(&self, part: Part, parsed: &Parsed) -> Result<Vec<String>, Error>;
+}
+
+/// The input of `S` in `parsed`, after checking that `params` only holds known keys.
+fn checked_input<'a, S: Solver>(
+    parsed: &'a Parsed,
+    params: &PuzzleParams,
+) -> Result<&'a S::Input, Error> {
+    let input = parsed
+        .0
+        .downcast_ref::<S::Input>()
+        .ok_or(Error::InputMismatch { day: S::DAY })?;
+
+    if let Some(key) = params.keys().find(|key| !S::PARAMS.contains(key)) {
+        return Err(Error::UnknownParam {
+            day: S::DAY,
+            key: key.to_string(),
+        });
+    }
+
+    Ok(input)
+}
+
+impl<S: Solver + Sync> DynSolver for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn params(&self) -> &'static [&'static str] {
+        S::PARAMS
+    }
+
+    fn parse_raw(&self, part: Part, input: &str) -> Result<Parsed, Error> {
+        let parsed = match part {
+            Part::One => Solver::parse(self, input)?,
+            Part::Two => self.parse_part2(input)?,
+        };
+
+        Ok(Parsed(Box::new(parsed)))
+    }
+
+    fn solve_within(
+        &self,
+        part: Part,
+        parsed: &Parsed,
+        params: &PuzzleParams,
+        budget: &Budget,
+    ) -> Result<Answer, Error> {
+        let input = checked_input::<S>(parsed, params)?;
+
+        match part {
+            Part::One => self.part1_within(input, params, budget),
+            Part::Two => self.part2_within(input, params, budget),
+        }
+    }
+
+    fn new_cache(&self) -> Option<Box<dyn DayCache>> {
+        Solver::new_cache(self)
+    }
+
+    fn solve_cached(
+        &self,
+        part: Part,
+        parsed: &Parsed,
+        params: &PuzzleParams,
+        budget: &Budget,
+        cache: Option<&mut dyn DayCache>,
+    ) -> Result<Answer, Error> {
+        let Some(cache) = cache else {
+            return self.solve_within(part, parsed, params, budget);
+        };
+        let input = checked_input::<S>(parsed, params)?;
+
+        match part {
+            Part::One => self.part1_cached(input, params, budget, cache),
+            Part::Two => self.part2_cached(input, params, budget, cache),
+        }
+    }
+
+    fn visualize(
+        &self,
+        part: Part,
+        parsed: &Parsed,
+        params: &PuzzleParams,
+        sink: &mut dyn FrameSink,
+    ) -> Result<Answer, Error> {
+        let input = checked_input::<S>(parsed, params)?;
+        Solver::visualize(self, input, part, params, sink)
+    }
+
+    fn graph(&self, part: Part, parsed: &Parsed, params: &PuzzleParams) -> Result<Graph, Error> {
+        let input = checked_input::<S>(parsed, params)?;
+        Solver::graph(self, input, part, params)
+    }
+
+    fn variants(&self, part: Part) -> Vec<VariantInfo> {
+        S::VARIANTS
+            .iter()
+            .filter(|variant| variant.part == part)
+            .map(|variant| VariantInfo {
+                name: variant.name,
+                approximate: variant.approximate,
+            })
+            .collect()
+    }
+
+    fn solve_variant(
+        &self,
+        part: Part,
+        name: &str,
+        parsed: &Parsed,
+        params: &PuzzleParams,
+    ) -> Result<Answer, Error> {
+        let input = checked_input::<S>(parsed, params)?;
+        let variant = S::VARIANTS
+            .iter()
+            .find(|variant| variant.part == part && variant.name == name)
+            .ok_or_else(|| Error::UnknownVariant {
+                day: S::DAY,
+                name: name.to_string(),
+            })?;
+
+        (variant.solve)(input, params)
+    }
+
+    fn validate(&self, part: Part, parsed: &Parsed) -> Result<Vec<String>, Error> {
+        let input = checked_input::<S>(parsed, &PuzzleParams::new())?;
+        Ok(Solver::validate(self, input, part))
+    }
+}
+
+static SOLVERS: [&dyn DynSolver; 25] = [
+    &day01::Day01,
+    &day02::Day02,
+    &day03::Day03,
+    &day04::Day04,
+    &day05::Day05,
+    &day06::Day06,
+    &day07::Day07,
+    &day08::Day08,
+    &day09::Day09,
+    &day10::Day10,
+    &day11::Day11,
+    &day12::Day12,
+    &day13::Day13,
+    &day14::Day14,
+    &day15::Day15,
+    &day16::Day16,
+    &day17::Day17,
+    &day18::Day18,
+    &day19::Day19,
+    &day20::Day20,
+    &day21::Day21,
+    &day22::Day22,
+    &day23::Day23,
+    &day24::Day24,
+    &day25::Day25,
+];
+
+/// All registered solvers, ordered by day.
+pub fn solvers() -> &'static [&'static dyn DynSolver] {
+    &SOLVERS
+}
+
+pub fn solver(day: u8) -> Result<&'static dyn DynSolver, Error> {
+    SOLVERS
+        .iter()
+        .find(|s| s.day() == day)
+        .copied()
+        .ok_or(Error::UnknownDay(day))
+}
+
+/// Parses `input` and solves `part` of `day`.
+pub fn solve(day: u8, part: Part, input: &str) -> Result<Answer, Error> {
+    solve_with(day, part, input, &PuzzleParams::default())
+}
+
+/// Like [`solve`], overriding the day's constants with `params`.
+pub fn solve_with(
+    day: u8,
+    part: Part,
+    input: &str,
+    params: &PuzzleParams,
+) -> Result<Answer, Error> {
+    let solver = solver(day)?;
+    let parsed = solver.parse(part, input)?;
+    solver.solve_with(part, &parsed, params)
+}
+
+/// Parses `input` and lists the assumptions of `part` of `day` that it breaks.
+pub fn validate(day: u8, part: Part, input: &str) -> Result<Vec<String>, Error> {
+    let solver = solver(day)?;
+    let parsed = solver.parse(part, input)?;
+    solver.validate(part, &parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve() {
+        let res = solve(1, Part::One, "3   4\n4   3\n2   5\n1   3\n3   9\n3   3");
+        assert_eq!(res, Ok(Answer::Int(11)));
+
+        let res = solve(25, Part::Two, "#####\n.....");
+        assert_eq!(res, Ok(Answer::None));
+    }
+
+    #[test]
+    fn test_registry() {
+        assert!(solvers().iter().map(|s| s.day()).eq(1..=25));
+        assert_eq!(solve(26, Part::One, "").err(), Some(Error::UnknownDay(26)));
+        assert_eq!(Part::try_from(3), Err(Error::UnknownPart(3)));
+    }
+
+    #[test]
+    fn test_input_mismatch() {
+        let parsed = solver(1)
+            .and_then(|s| s.parse(Part::One, "3   4"))
+            .expect("valid input");
+        let res = solver(9).and_then(|s| s.solve(Part::One, &parsed));
+        assert_eq!(res, Err(Error::InputMismatch { day: 9 }));
+    }
+
+    #[test]
+    fn test_params() {
+        let params = PuzzleParams::new().with("blinks", 6);
+        let res = solve_with(11, Part::One, "125 17", &params);
+        assert_eq!(res, Ok(Answer::Int(22)));
+
+        let res = solve_with(1, Part::One, "3   4", &params);
+        assert_eq!(
+            res,
+            Err(Error::UnknownParam {
+                day: 1,
+                key: "blinks".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,350 @@
+use crate::error::Error;
+use crate::grid::Grid;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+/// One snapshot of a simulation, as a grid of glyphs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub caption: String,
+    pub grid: Grid<char>,
+}
+
+impl Frame {
+    pub fn new(caption: impl Into<String>, grid: Grid<char>) -> Self {
+        Self {
+            caption: caption.into(),
+            grid,
+        }
+    }
+
+    /// Renders `grid` by mapping each tile to a glyph.
+    pub fn from_grid<T>(
+        caption: impl Into<String>,
+        grid: &Grid<T>,
+        glyph: impl Fn(&T) -> char,
+    ) -> Self {
+        Self::new(
+            caption,
+            Grid::from_fn(grid.rows, grid.columns, |pos| glyph(&grid[pos])),
+        )
+    }
+}
+
+/// Receives the frames a solver emits while it runs.
+///
+/// Sinks never fail the solver: output errors are kept and reported by the
+/// sink's owner once the run is over.
+pub trait FrameSink {
+    /// Whether frames are wanted at all, so that solvers can skip building them.
+    fn enabled(&self) -> bool {
+        true
+    }
+
+    fn emit(&mut self, frame: &Frame);
+}
+
+/// Discards all frames; used by the plain solving entry points.
+pub struct NoViz;
+
+impl FrameSink for NoViz {
+    fn enabled(&self) -> bool {
+        false
+    }
+
+    fn emit(&mut self, _frame: &Frame) {}
+}
+
+/// Forwards every `every`-th frame to `inner`.
+pub struct Sample<'a> {
+    inner: &'a mut dyn FrameSink,
+    every: usize,
+    seen: usize,
+}
+
+impl<'a> Sample<'a> {
+    pub fn new(inner: &'a mut dyn FrameSink, every: usize) -> Self {
+        Self {
+            inner,
+            every: every.max(1),
+            seen: 0,
+        }
+    }
+}
+
+impl FrameSink for Sample<'_> {
+    fn enabled(&self) -> bool {
+        self.inner.enabled()
+    }
+
+    fn emit(&mut self, frame: &Frame) {
+        if self.seen.is_multiple_of(self.every) {
+            self.inner.emit(frame);
+        }
+        self.seen += 1;
+    }
+}
+
+/// Collects frames in memory, mostly for tests.
+#[derive(Default)]
+pub struct Recorder {
+    pub frames: Vec<Frame>,
+}
+
+impl FrameSink for Recorder {
+    fn emit(&mut self, frame: &Frame) {
+        self.frames.push(frame.clone());
+    }
+}
+
+/// Prints each frame as text, preceded by its caption.
+pub struct AsciiSink<W: Write> {
+    out: W,
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write> AsciiSink<W> {
+    pub const fn new(out: W) -> Self {
+        Self { out, error: None }
+    }
+
+    pub fn finish(self) -> Result<(), Error> {
+        self.error.map_or(Ok(()), |e| {
+            Err(Error::Io {
+                path: "<output>".to_string(),
+                message: e.to_string(),
+            })
+        })
+    }
+
+    fn write(&mut self, frame: &Frame) -> std::io::Result<()> {
+        writeln!(self.out, "{}", frame.caption)?;
+        for row in &frame.grid {
+            writeln!(self.out, "{}", row.iter().collect::<String>())?;
+        }
+        writeln!(self.out)
+    }
+}
+
+impl<W: Write> FrameSink for AsciiSink<W> {
+    fn emit(&mut self, frame: &Frame) {
+        if self.error.is_none() {
+            self.error = self.write(frame).err();
+        }
+    }
+}
+
+pub type Rgb = [u8; 3];
+
+/// Colors for each glyph, falling back to a default for unlisted ones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Palette {
+    colors: HashMap<char, Rgb>,
+    fallback: Rgb,
+}
+
+impl Palette {
+    pub fn new(fallback: Rgb) -> Self {
+        Self {
+            colors: HashMap::new(),
+            fallback,
+        }
+    }
+
+    #[must_use]
+    pub fn with(mut self, glyph: char, color: Rgb) -> Self {
+        self.colors.insert(glyph, color);
+        self
+    }
+
+    pub fn color(&self, glyph: char) -> Rgb {
+        self.colors.get(&glyph).copied().unwrap_or(self.fallback)
+    }
+}
+
+impl Default for Palette {
+    /// Colors for the glyphs used by the grid days; digits, such as robot
+    /// counts, are drawn in white.
+    fn default() -> Self {
+        let palette = Self::new([255, 255, 255])
+            .with('.', [0, 0, 0])
+            .with(' ', [0, 0, 0])
+            .with('#', [128, 128, 128])
+            .with('O', [200, 140, 40])
+            .with('[', [200, 140, 40])
+            .with(']', [200, 140, 40])
+            .with('@', [220, 40, 40])
+            .with('X', [40, 80, 200]);
+
+        ['^', '>', 'v', '<']
+            .into_iter()
+            .fold(palette, |palette, glyph| palette.with(glyph, [220, 40, 40]))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    #[cfg(feature = "png")]
+    Png,
+}
+
+impl ImageFormat {
+    const fn extension(self) -> &'static str {
+        match self {
+            Self::Ppm => "ppm",
+            #[cfg(feature = "png")]
+            Self::Png => "png",
+        }
+    }
+}
+
+/// Writes each frame as a numbered image into a directory.
+pub struct ImageSink {
+    dir: PathBuf,
+    format: ImageFormat,
+    palette: Palette,
+    scale: usize,
+    written: usize,
+    error: Option<Error>,
+}
+
+impl ImageSink {
+    /// Every tile becomes a `scale` x `scale` square of pixels.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        format: ImageFormat,
+        palette: Palette,
+        scale: usize,
+    ) -> Self {
+        Self {
+            dir: dir.into(),
+            format,
+            palette,
+            scale: scale.max(1),
+            written: 0,
+            error: None,
+        }
+    }
+
+    /// The number of frames written, or the first error.
+    pub fn finish(self) -> Result<usize, Error> {
+        self.error.map_or(Ok(self.written), Err)
+    }
+
+    fn pixels(&self, grid: &Grid<char>) -> Vec<u8> {
+        let mut pixels = Vec::with_capacity(grid.rows * grid.columns * self.scale * self.scale * 3);
+        for row in grid {
+            for _ in 0..self.scale {
+                for &glyph in row {
+                    let color = self.palette.color(glyph);
+                    for _ in 0..self.scale {
+                        pixels.extend_from_slice(&color);
+                    }
+                }
+            }
+        }
+        pixels
+    }
+
+    fn write(&self, frame: &Frame) -> Result<(), Error> {
+        let (width, height) = (
+            frame.grid.columns * self.scale,
+            frame.grid.rows * self.scale,
+        );
+        let pixels = self.pixels(&frame.grid);
+
+        let path = self.dir.join(format!(
+            "frame_{:05}.{}",
+            self.written,
+            self.format.extension()
+        ));
+        std::fs::create_dir_all(&self.dir).map_err(|e| Error::io(&self.dir, &e))?;
+        let file = std::fs::File::create(&path).map_err(|e| Error::io(&path, &e))?;
+        let mut out = std::io::BufWriter::new(file);
+
+        match self.format {
+            ImageFormat::Ppm => write!(out, "P6\n{width} {height}\n255\n")
+                .and_then(|()| out.write_all(&pixels))
+                .and_then(|()| out.flush())
+                .map_err(|e| Error::io(&path, &e)),
+            #[cfg(feature = "png")]
+            ImageFormat::Png => {
+                let size = |n: usize| {
+                    u32::try_from(n).map_err(|_| Error::Io {
+                        path: path.display().to_string(),
+                        message: "frame too large".to_string(),
+                    })
+                };
+                let mut encoder = png::Encoder::new(out, size(width)?, size(height)?);
+                encoder.set_color(png::ColorType::Rgb);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder
+                    .write_header()
+                    .and_then(|mut writer| writer.write_image_data(&pixels))
+                    .map_err(|e| Error::Io {
+                        path: path.display().to_string(),
+                        message: e.to_string(),
+                    })
+            }
+        }
+    }
+}
+
+impl FrameSink for ImageSink {
+    fn emit(&mut self, frame: &Frame) {
+        if self.error.is_some() {
+            return;
+        }
+
+        match self.write(frame) {
+            Ok(()) => self.written += 1,
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(caption: &str) -> Frame {
+        let grid = Grid::from_vec(2, 3, "#.@X^?".chars().collect()).expect("2x3 grid");
+        Frame::new(caption, grid)
+    }
+
+    #[test]
+    fn test_ascii() {
+        let mut out = vec![];
+        let mut sink = AsciiSink::new(&mut out);
+        sink.emit(&frame("step 1"));
+        sink.finish().expect("written to memory");
+
+        assert_eq!(String::from_utf8_lossy(&out), "step 1\n#.@\nX^?\n\n");
+    }
+
+    #[test]
+    fn test_sample() {
+        let mut recorder = Recorder::default();
+        let mut sink = Sample::new(&mut recorder, 2);
+        for i in 0..5 {
+            sink.emit(&frame(&i.to_string()));
+        }
+
+        let captions = recorder.frames.iter().map(|f| f.caption.as_str());
+        assert!(captions.eq(["0", "2", "4"]));
+    }
+
+    #[test]
+    fn test_pixels() {
+        let sink = ImageSink::new("unused", ImageFormat::Ppm, Palette::default(), 2);
+        let pixels = sink.pixels(&frame("").grid);
+
+        assert_eq!(pixels.len(), 2 * 3 * 4 * 3);
+        assert_eq!(pixels[0..6], [128, 128, 128, 128, 128, 128]);
+        assert_eq!(pixels[6..9], [0, 0, 0]);
+        // `?` is not in the palette
+        assert_eq!(pixels[pixels.len() - 3..], [255, 255, 255]);
+    }
+}